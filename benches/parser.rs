@@ -0,0 +1,67 @@
+use async_std::task;
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use async_httype::{Request, read_chunked_stream, read_head, read_head_buffered, relay_sized_stream};
+
+fn request_read(c: &mut Criterion) {
+    let small = String::from("GET /path?query=1 HTTP/1.1\r\nHost: example.com\r\nAccept: */*\r\n\r\n");
+    let mut large = String::from("GET /path?query=1 HTTP/1.1\r\n");
+    for i in 0..100 {
+        large.push_str(&format!("X-Header-{}: value-{}\r\n", i, i));
+    }
+    large.push_str("\r\n");
+
+    let mut group = c.benchmark_group("request_read");
+    for (name, data) in &[("small", small), ("large_headers", large)] {
+        group.throughput(Throughput::Bytes(data.len() as u64));
+        group.bench_function(*name, |b| b.iter(|| {
+            task::block_on(Request::read(&mut data.as_bytes(), None)).unwrap()
+        }));
+    }
+    group.finish();
+}
+
+fn head_read(c: &mut Criterion) {
+    let data = format!("GET /{} HTTP/1.1\r\n", "a".repeat(200));
+
+    let mut group = c.benchmark_group("read_head");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("bytes", |b| b.iter(|| {
+        task::block_on(read_head(&mut data.as_bytes(), &mut Vec::new())).unwrap()
+    }));
+    group.bench_function("buffered", |b| b.iter(|| {
+        task::block_on(read_head_buffered(&mut data.as_bytes(), &mut Vec::new())).unwrap()
+    }));
+    group.finish();
+}
+
+fn chunked_read(c: &mut Criterion) {
+    let chunk = "x".repeat(1024);
+    let mut data = String::new();
+    for _ in 0..1024 {
+        data.push_str(&format!("{:x}\r\n{}\r\n", chunk.len(), chunk));
+    }
+    data.push_str("0\r\n\r\n");
+
+    let mut group = c.benchmark_group("read_chunked_stream");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("1MiB", |b| b.iter(|| {
+        let mut source = Vec::new();
+        task::block_on(read_chunked_stream(&mut data.as_bytes(), &mut source, None)).unwrap()
+    }));
+    group.finish();
+}
+
+fn sized_relay(c: &mut Criterion) {
+    let data = vec![0u8; 1024 * 1024];
+
+    let mut group = c.benchmark_group("relay_sized_stream");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("1MiB", |b| b.iter(|| {
+        let mut output = Vec::with_capacity(data.len());
+        task::block_on(relay_sized_stream(&mut data.as_slice(), &mut output, data.len(), None)).unwrap()
+    }));
+    group.finish();
+}
+
+criterion_group!(benches, request_read, head_read, chunked_read, sized_relay);
+criterion_main!(benches);
@@ -0,0 +1,18 @@
+use async_std::io;
+use async_httype::{Body, Request};
+
+#[async_std::main]
+async fn main() {
+    let mut stdin = io::stdin();
+    let req = Request::read(&mut stdin, None).await.expect("invalid request");
+    let mut body = Body::new();
+    if req.has_body() {
+        body.read(&mut stdin, req.headers()).await.expect("invalid body");
+    }
+
+    println!("{} {}", req.method(), req.uri());
+    for (name, value) in req.sorted_headers() {
+        println!("{}: {}", name, value);
+    }
+    println!("{} body bytes", body.length());
+}
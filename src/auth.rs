@@ -0,0 +1,84 @@
+use crate::{Error};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AuthScheme {
+    Basic { username: String, password: String },
+    Bearer(String),
+    Other(String, String),
+}
+
+impl AuthScheme {
+
+    pub fn parse(value: &str) -> Result<Self, Error> {
+        let mut parts = value.trim().splitn(2, ' ');
+        let scheme = parts.next().unwrap_or("");
+        let credentials = parts.next().unwrap_or("").trim();
+
+        if scheme.eq_ignore_ascii_case("Basic") {
+            let decoded = match decode_base64(credentials).map(String::from_utf8) {
+                Some(Ok(decoded)) => decoded,
+                _ => return Err(Error::InvalidHeader(String::from("Authorization"))),
+            };
+            let mut pair = decoded.splitn(2, ':');
+            match (pair.next(), pair.next()) {
+                (Some(username), Some(password)) => Ok(AuthScheme::Basic {
+                    username: username.to_string(),
+                    password: password.to_string(),
+                }),
+                _ => Err(Error::InvalidHeader(String::from("Authorization"))),
+            }
+        } else if scheme.eq_ignore_ascii_case("Bearer") && !credentials.is_empty() {
+            Ok(AuthScheme::Bearer(credentials.to_string()))
+        } else if !scheme.is_empty() {
+            Ok(AuthScheme::Other(scheme.to_string(), credentials.to_string()))
+        } else {
+            Err(Error::InvalidHeader(String::from("Authorization")))
+        }
+    }
+}
+
+fn decode_base64(value: &str) -> Option<Vec<u8>> {
+    let value = value.trim_end_matches('=');
+    let mut output = Vec::with_capacity(value.len() * 3 / 4);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+
+    for byte in value.bytes() {
+        let sextet = match byte {
+            b'A'..=b'Z' => byte - b'A',
+            b'a'..=b'z' => byte - b'a' + 26,
+            b'0'..=b'9' => byte - b'0' + 52,
+            b'+' => 62,
+            b'/' => 63,
+            _ => return None,
+        };
+        buffer = (buffer << 6) | sextet as u32;
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            output.push((buffer >> bits) as u8);
+        }
+    }
+    if bits >= 6 { // single dangling character
+        return None;
+    }
+
+    Some(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn parses_basic_and_bearer() {
+        assert_eq!(AuthScheme::parse("Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==").unwrap(), AuthScheme::Basic {
+            username: String::from("Aladdin"),
+            password: String::from("open sesame"),
+        });
+        assert_eq!(AuthScheme::parse("bearer abc.def").unwrap(), AuthScheme::Bearer(String::from("abc.def")));
+        assert_eq!(AuthScheme::parse("Digest x=1").unwrap(), AuthScheme::Other(String::from("Digest"), String::from("x=1")));
+        assert_eq!(AuthScheme::parse("Basic Q!==").unwrap_err(), Error::InvalidHeader(String::from("Authorization")));
+        assert_eq!(AuthScheme::parse("Basic dXNlcg==").unwrap_err(), Error::InvalidHeader(String::from("Authorization")));
+    }
+}
@@ -1,12 +1,37 @@
 use std::fmt;
+use std::convert::TryFrom;
+use std::pin::Pin;
+use std::future::Future;
+use std::task::{Context, Poll};
+use std::time::Duration;
 use std::collections::HashMap;
-use async_std::io::{Read, Write};
-use crate::{Error, read_chunked_stream, read_sized_stream, write_to_stream, flush_stream};
+#[cfg(feature = "digest")]
+use sha2::{Digest, Sha256};
+use async_std::io::{self, Read, ReadExt, Write};
+use crate::{Error, Request, Response, MinRateStream, ReaderConfig, body_expected, is_chunked, read_chunked_stream_with_sizes, read_chunked_stream_with_callback, read_sized_stream, read_sized_stream_with_callback, drain_chunked_stream, drain_sized_stream, parse_content_length,
+    relay_sized_stream, write_to_stream, flush_stream};
+
+macro_rules! read_primed { // runs a framed reader over the primed bytes, then the stream
+    ($body:ident, $stream:ident, $read:ident($($arg:expr),*)) => {{
+        let primed = std::mem::take(&mut $body.primed);
+        let mut prefix = primed.as_slice();
+        let result = $body.$read(&mut (&mut prefix).chain(&mut *$stream), $($arg),*).await;
+        $body.primed = prefix.to_vec(); // bytes past the body stay primed
+        result
+    }};
+}
 
 pub struct Body {
     bytes: Vec<u8>,
     length: usize,
+    chunks: Vec<usize>,
     length_limit: Option<usize>,
+    chunks_limit: Option<usize>,
+    min_read_rate: Option<(usize, Duration)>,
+    complete: bool,
+    primed: Vec<u8>,
+    #[cfg(feature = "digest")]
+    hasher: Option<Sha256>,
 }
 
 impl Body {
@@ -15,7 +40,14 @@ impl Body {
         Self {
             bytes: Vec::new(),
             length: 0,
+            chunks: Vec::new(),
             length_limit: None,
+            chunks_limit: None,
+            min_read_rate: None,
+            complete: false,
+            primed: Vec::new(),
+            #[cfg(feature = "digest")]
+            hasher: None,
         }
     }
 
@@ -23,10 +55,65 @@ impl Body {
         &self.bytes
     }
 
+    pub fn set_bytes<B: Into<Vec<u8>>>(&mut self, bytes: B) {
+        self.bytes = bytes.into();
+        self.length = self.bytes.len();
+        self.chunks.clear();
+    }
+
+    pub fn append(&mut self, data: &[u8]) -> Result<(), Error> {
+        if let Some(limit) = self.length_limit {
+            if self.length + data.len() > limit {
+                return Err(Error::SizeLimitExceeded(limit));
+            }
+        }
+        self.bytes.extend_from_slice(data);
+        self.length += data.len();
+        Ok(())
+    }
+
+    pub fn reader(&self) -> &[u8] {
+        &self.bytes
+    }
+
     pub fn length(&self) -> usize {
         self.length
     }
 
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn digest(&self) -> Option<[u8; 32]> {
+        self.hasher.as_ref().map(|hasher| hasher.clone().finalize().into())
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn has_digest(&self) -> bool {
+        self.hasher.is_some()
+    }
+
+    #[cfg(feature = "digest")]
+    pub fn set_digest(&mut self, value: bool) {
+        self.hasher = match value {
+            true => Some(Sha256::new()),
+            false => None,
+        };
+    }
+
+    pub fn primed(&self) -> &[u8] {
+        &self.primed
+    }
+
+    pub fn prime(&mut self, prefix: &[u8]) {
+        self.primed.extend_from_slice(prefix);
+    }
+
+    pub fn chunks(&self) -> &Vec<usize> {
+        &self.chunks
+    }
+
     pub fn length_limit(&self) -> Option<usize> {
         self.length_limit
     }
@@ -43,41 +130,225 @@ impl Body {
         self.length_limit = None;
     }
 
+    pub fn chunks_limit(&self) -> Option<usize> {
+        self.chunks_limit
+    }
+
+    pub fn has_chunks_limit(&self) -> bool {
+        self.chunks_limit.is_some()
+    }
+
+    pub fn set_chunks_limit(&mut self, limit: usize) {
+        self.chunks_limit = Some(limit);
+    }
+
+    pub fn remove_chunks_limit(&mut self) {
+        self.chunks_limit = None;
+    }
+
+    pub fn min_read_rate(&self) -> Option<(usize, Duration)> {
+        self.min_read_rate
+    }
+
+    pub fn has_min_read_rate(&self) -> bool {
+        self.min_read_rate.is_some()
+    }
+
+    pub fn set_min_read_rate(&mut self, bytes: usize, interval: Duration) {
+        self.min_read_rate = Some((bytes, interval));
+    }
+
+    pub fn remove_min_read_rate(&mut self) {
+        self.min_read_rate = None;
+    }
+
     pub async fn read<I>(&mut self, stream: &mut I, res: &HashMap<String, String>) -> Result<usize, Error>
         where
         I: Read + Unpin,
+    {
+        self.read_limited(stream, res, self.length_limit).await
+    }
+
+    async fn read_limited<I>(&mut self, stream: &mut I, res: &HashMap<String, String>, limit: Option<usize>) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
     {
         let length = res.get("Content-Length");
         let encoding = res.get("Transfer-Encoding");
+        self.complete = false;
 
-        if encoding.is_some() && encoding.unwrap().contains(&String::from("chunked")) {
-            self.read_chunked(stream).await
+        if encoding.is_some_and(|encoding| is_chunked(encoding)) {
+            read_primed!(self, stream, read_chunked_from(limit))
         } else {
             let length = match length {
-                Some(length) => match length.parse::<usize>() {
+                Some(length) => match usize::try_from(parse_content_length(length)?) {
                     Ok(length) => length,
-                    Err(_) => return Err(Error::InvalidHeader(String::from("Content-Length"))),
+                    Err(_) => return Err(Error::SizeLimitExceeded(usize::MAX)),
                 },
                 None => return Err(Error::InvalidHeader(String::from("Content-Length"))),
             };
-            self.read_sized(stream, length).await
+            read_primed!(self, stream, read_sized_from(length, limit))
+        }
+    }
+
+    pub async fn read_decoded<I, F>(&mut self, stream: &mut I, res: &HashMap<String, String>, mut decode: F) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+        F: FnMut(&str, Vec<u8>) -> Result<Vec<u8>, Error>,
+    {
+        self.read(stream, res).await?; // transfer coding first
+
+        if let Some(encoding) = res.get("Content-Encoding") {
+            let codings: Vec<&str> = encoding.split(',').map(|coding| coding.trim()).filter(|coding| !coding.is_empty()).collect();
+            let mut bytes = std::mem::take(&mut self.bytes);
+            for coding in codings.iter().rev() { // applied in listed order, undone in reverse
+                if !coding.eq_ignore_ascii_case("identity") {
+                    bytes = decode(coding, bytes)?;
+                }
+            }
+            self.length = bytes.len();
+            self.bytes = bytes;
+        }
+
+        Ok(self.length)
+    }
+
+    pub async fn read_response<I>(&mut self, stream: &mut I, method: &str, res: &Response) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+    {
+        if body_expected(method, res.status_code()) {
+            self.read(stream, res.headers()).await
+        } else {
+            self.complete = true;
+            Ok(0)
+        }
+    }
+
+    pub async fn read_request<I, F, Fut>(&mut self, stream: &mut I, req: &Request, send_continue: F) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+        F: FnOnce() -> Fut,
+        Fut: Future<Output = Result<usize, Error>>,
+    {
+        if !req.has_body() {
+            self.complete = true;
+            return Ok(0);
+        }
+        if req.expects_continue() && self.length == 0 { // deferred until the body is actually wanted
+            send_continue().await?;
         }
+        self.read(stream, req.headers()).await
+    }
+
+    pub async fn read_with<I>(&mut self, stream: &mut I, res: &HashMap<String, String>, config: &mut ReaderConfig) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+    {
+        let budget = match (config.remaining(), self.length_limit) {
+            (Some(remaining), Some(limit)) if limit <= self.length.saturating_add(remaining) => None, // the body's own limit is tighter
+            (remaining, _) => remaining.map(|remaining| self.length.saturating_add(remaining)),
+        };
+
+        let length = match self.read_limited(stream, res, budget.or(self.length_limit)).await {
+            Ok(length) => length,
+            Err(Error::SizeLimitExceeded(_)) if budget.is_some() => return Err(Error::SizeLimitExceeded(config.length_limit().unwrap_or(0))),
+            Err(err) => return Err(err),
+        };
+        config.consume(length)?;
+
+        Ok(length)
+    }
+
+    pub async fn read_with_limit_callback<I, F>(&mut self, stream: &mut I, res: &HashMap<String, String>, callback: F) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+        F: FnMut(&[u8]),
+    {
+        read_primed!(self, stream, read_with_limit_callback_from(res, callback))
+    }
+
+    async fn read_with_limit_callback_from<I, F>(&mut self, stream: &mut I, res: &HashMap<String, String>, mut callback: F) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+        F: FnMut(&[u8]),
+    {
+        let length = res.get("Content-Length");
+        let encoding = res.get("Transfer-Encoding");
+        self.complete = false;
+
+        let limit = self.length_limit;
+        let start = self.length;
+        let chunks = self.chunks.len();
+        let bytes = &mut self.bytes;
+        let mut received = 0;
+        let mut sink = |data: &[u8]| {
+            callback(data);
+            received += data.len();
+            let within = match limit {
+                Some(limit) => start + received <= limit,
+                None => true,
+            };
+            if within {
+                bytes.extend_from_slice(data);
+            }
+        };
+
+        let mut stream = MinRateStream::new(stream, self.min_read_rate);
+        let result = if encoding.is_some_and(|encoding| is_chunked(encoding)) {
+            read_chunked_stream_with_callback(&mut stream, &mut self.chunks, None, self.chunks_limit, &mut sink).await.map(|(_, complete)| complete)
+        } else {
+            match length {
+                Some(length) => match parse_content_length(length) {
+                    Ok(length) => read_sized_stream_with_callback(&mut stream, length, &mut sink).await.map(|_| true),
+                    Err(err) => Err(err),
+                },
+                None => Err(Error::InvalidHeader(String::from("Content-Length"))),
+            }
+        };
+
+        let result = match limit {
+            Some(limit) if result.is_ok() && start + received > limit => Err(Error::SizeLimitExceeded(limit)),
+            _ => result,
+        };
+        let complete = match result {
+            Ok(complete) => complete,
+            Err(err) => {
+                self.bytes.truncate(start);
+                self.chunks.truncate(chunks);
+                return Err(err);
+            },
+        };
+        self.update_digest(received);
+        self.length += received;
+        self.complete = complete;
+
+        Ok(received)
     }
 
     pub async fn read_chunked<I>(&mut self, stream: &mut I) -> Result<usize, Error>
         where
         I: Read + Unpin,
     {
-        let limit = match self.length_limit {
+        read_primed!(self, stream, read_chunked_from(self.length_limit))
+    }
+
+    async fn read_chunked_from<I>(&mut self, stream: &mut I, limit: Option<usize>) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+    {
+        let limit = match limit {
             Some(limit) => match limit == 0 {
                 true => return Err(Error::SizeLimitExceeded(limit)),
-                false => Some(limit - self.length),
+                false => Some(limit.saturating_sub(self.length)),
             },
             None => None,
         };
         
-        let length = read_chunked_stream(stream, &mut self.bytes, limit).await?;
+        let (length, complete) = read_chunked_stream_with_sizes(&mut MinRateStream::new(stream, self.min_read_rate), &mut self.bytes, &mut self.chunks, limit, self.chunks_limit).await?;
+        self.update_digest(length);
         self.length += length;
+        self.complete = complete;
 
         Ok(length)
     }
@@ -86,38 +357,434 @@ impl Body {
         where
         I: Read + Unpin,
     {
-        match self.length_limit {
-            Some(limit) => match length + self.length > limit {
-                true => return Err(Error::SizeLimitExceeded(limit)),
-                false => (),
-            },
-            None => (),
-        };
+        read_primed!(self, stream, read_sized_from(length, self.length_limit))
+    }
+
+    async fn read_sized_from<I>(&mut self, stream: &mut I, length: usize, limit: Option<usize>) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+    {
+        if let Some(limit) = limit {
+            match length.checked_add(self.length) {
+                Some(total) if total <= limit => (),
+                _ => return Err(Error::SizeLimitExceeded(limit)),
+            };
+        }
 
-        let length = read_sized_stream(stream, &mut self.bytes, length).await?;
+        let length = read_sized_stream(&mut MinRateStream::new(stream, self.min_read_rate), &mut self.bytes, length).await?;
+        self.update_digest(length);
         self.length += length;
+        self.complete = true;
 
         Ok(length)
     }
     
+    pub async fn drain<I>(&mut self, stream: &mut I, res: &HashMap<String, String>) -> Result<u64, Error>
+        where
+        I: Read + Unpin,
+    {
+        read_primed!(self, stream, drain_from(res))
+    }
+
+    async fn drain_from<I>(&mut self, stream: &mut I, res: &HashMap<String, String>) -> Result<u64, Error>
+        where
+        I: Read + Unpin,
+    {
+        let length = res.get("Content-Length");
+        let encoding = res.get("Transfer-Encoding");
+
+        if encoding.is_some_and(|encoding| is_chunked(encoding)) {
+            let length = drain_chunked_stream(&mut MinRateStream::new(stream, self.min_read_rate), self.length_limit, self.chunks_limit).await?;
+            Ok(length as u64)
+        } else {
+            let length = match length {
+                Some(length) => parse_content_length(length)?,
+                None => return Err(Error::InvalidHeader(String::from("Content-Length"))),
+            };
+            if let Some(limit) = self.length_limit {
+                if length > limit as u64 {
+                    return Err(Error::SizeLimitExceeded(limit));
+                }
+            }
+            drain_sized_stream(&mut MinRateStream::new(stream, self.min_read_rate), length).await
+        }
+    }
+
     pub async fn write<I>(&mut self, stream: &mut I) -> Result<usize, Error>
         where
         I: Write + Unpin,
     {
-        let size = write_to_stream(stream, &self.bytes()).await?;
+        let size = write_to_stream(stream, self.bytes()).await?;
         flush_stream(stream).await?;
         Ok(size)
     }
 
+    #[cfg(feature = "digest")]
+    fn update_digest(&mut self, length: usize) {
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(&self.bytes[self.bytes.len() - length..]);
+        }
+    }
+
+    #[cfg(not(feature = "digest"))]
+    fn update_digest(&mut self, _: usize) {}
+
+    pub async fn write_from_reader<O, R>(stream: &mut O, reader: &mut R, length: usize) -> Result<usize, Error>
+        where
+        O: Write + Unpin,
+        R: Read + Unpin,
+    {
+        let size = relay_sized_stream(reader, stream, length, None).await?;
+        if size != length { // source shorter than declared
+            return Err(Error::InvalidData);
+        }
+        Ok(size)
+    }
+
     pub fn clear(&mut self) {
         self.bytes.clear();
         self.length = 0;
+        self.chunks.clear();
         self.length_limit = None;
+        self.chunks_limit = None;
+        self.min_read_rate = None;
+        self.complete = false;
+        self.primed.clear();
+        #[cfg(feature = "digest")]
+        {
+            self.hasher = None;
+        }
+    }
+}
+
+impl Default for Body {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<Vec<u8>> for Body {
+    fn from(item: Vec<u8>) -> Self {
+        let mut body = Self::new();
+        body.set_bytes(item);
+        body
+    }
+}
+
+impl From<&str> for Body {
+    fn from(item: &str) -> Self {
+        Self::from(item.as_bytes().to_vec())
+    }
+}
+
+impl From<String> for Body {
+    fn from(item: String) -> Self {
+        Self::from(item.into_bytes())
+    }
+}
+
+impl Write for Body {
+    fn poll_write(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        match self.append(buf) {
+            Ok(()) => Poll::Ready(Ok(buf.len())),
+            Err(_) => Poll::Ready(Err(io::Error::other("size limit exceeded"))),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
     }
 }
 
 impl fmt::Display for Body {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{:?}", self.bytes())
+        write!(fmt, "{}", String::from_utf8_lossy(self.bytes()))
+    }
+}
+
+impl fmt::Debug for Body {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        fmt.debug_struct("Body")
+            .field("bytes", &self.bytes)
+            .field("length", &self.length)
+            .field("chunks", &self.chunks)
+            .field("complete", &self.complete)
+            .field("primed", &self.primed)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn writes_into_body() {
+        let mut body = Body::new();
+        io::copy(&mut "Hello".as_bytes(), &mut body).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        assert_eq!(body.length(), 5);
+        body.set_length_limit(8);
+        assert!(io::copy(&mut " World".as_bytes(), &mut body).await.is_err());
+    }
+
+    #[async_std::test]
+    async fn reads_from_body() {
+        let mut body = Body::new();
+        io::copy(&mut "Hello".as_bytes(), &mut body).await.unwrap();
+        let mut output = Vec::new();
+        io::copy(&mut body.reader(), &mut output).await.unwrap();
+        assert_eq!(output, b"Hello");
+    }
+
+    #[async_std::test]
+    async fn creates_from_bytes() {
+        let mut body = Body::from("Hello");
+        assert_eq!(body.bytes(), b"Hello");
+        assert_eq!(body.length(), 5);
+        body.set_bytes(vec![1, 2]);
+        assert_eq!(body.length(), 2);
+        assert_eq!(Body::from(String::from("Hi")).length(), 2);
+    }
+
+    #[async_std::test]
+    async fn detects_truncated_body() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        let mut body = Body::new();
+        body.read(&mut "5\r\nHello\r\n".as_bytes(), &headers).await.unwrap();
+        assert!(!body.is_complete());
+        body.read(&mut "0\r\n\r\n".as_bytes(), &headers).await.unwrap();
+        assert!(body.is_complete());
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("2"));
+        let mut body = Body::new();
+        body.read(&mut "Hi".as_bytes(), &headers).await.unwrap();
+        assert!(body.is_complete());
+    }
+
+    #[async_std::test]
+    async fn streams_oversized_body_to_callback() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        let mut body = Body::new();
+        body.set_length_limit(4);
+        let mut seen = Vec::new();
+        let res = body.read_with_limit_callback(&mut "5\r\nHello\r\n3\r\nBye\r\n0\r\n\r\n".as_bytes(), &headers, |data| seen.extend_from_slice(data)).await;
+        assert_eq!(res, Err(Error::SizeLimitExceeded(4)));
+        assert_eq!(seen, b"HelloBye");
+        assert_eq!(body.length(), 0);
+
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("2"));
+        let mut seen = Vec::new();
+        body.read_with_limit_callback(&mut "Hi".as_bytes(), &headers, |data| seen.extend_from_slice(data)).await.unwrap();
+        assert_eq!(seen, b"Hi");
+        assert_eq!(body.bytes(), b"Hi");
+    }
+
+    #[async_std::test]
+    async fn discards_bytes_of_failed_callback_read() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        let mut body = Body::new();
+        body.set_length_limit(20);
+        let mut seen = Vec::new();
+        let res = body.read_with_limit_callback(&mut "5\r\nHello\r\n3\r\nBy".as_bytes(), &headers, |data| seen.extend_from_slice(data)).await;
+        assert!(res.is_err());
+        assert_eq!(seen, b"Hello");
+        assert_eq!(body.length(), 0);
+        assert_eq!(body.bytes(), b"");
+        assert_eq!(body.chunks().len(), 0);
+    }
+
+    #[async_std::test]
+    async fn rejects_mismatched_framing() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("10"));
+        let res = Body::new().read(&mut "Hello".as_bytes(), &headers).await;
+        assert_eq!(res, Err(Error::InvalidData));
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        let res = Body::new().read(&mut "Hello\r\n".as_bytes(), &headers).await;
+        assert_eq!(res, Err(Error::InvalidData));
+    }
+
+    #[async_std::test]
+    async fn rejects_huge_content_length_without_allocating() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("18446744073709551615"));
+        let mut body = Body::new();
+        assert!(body.read(&mut "Hello".as_bytes(), &headers).await.is_err());
+        assert_eq!(body.length(), 0);
+        assert!(body.bytes().is_empty());
+    }
+
+    #[async_std::test]
+    async fn reads_sized_body_as_it_arrives() {
+        let mut body = Body::new();
+        body.set_bytes("Hi");
+        assert_eq!(body.read_sized(&mut "Hello".as_bytes(), 10).await, Err(Error::InvalidData));
+        assert_eq!(body.bytes(), b"Hi");
+        body.set_length_limit(10);
+        assert_eq!(body.read_sized(&mut "Hello".as_bytes(), usize::MAX).await, Err(Error::SizeLimitExceeded(10)));
+        let data = "x".repeat(5000);
+        assert_eq!(body.read_sized(&mut data.as_bytes(), 4000).await, Err(Error::SizeLimitExceeded(10)));
+        body.remove_length_limit();
+        assert_eq!(body.read_sized(&mut data.as_bytes(), 4000).await, Ok(4000));
+        assert_eq!(body.length(), 4002);
+    }
+
+    #[async_std::test]
+    async fn decodes_transfer_before_content_coding() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        headers.insert(String::from("Content-Encoding"), String::from("x-reverse, x-upper"));
+        let mut body = Body::new();
+        let mut order = Vec::new();
+        let length = body.read_decoded(&mut "3\r\nOLL\r\n2\r\nEH\r\n0\r\n\r\n".as_bytes(), &headers, |coding, bytes| {
+            order.push(coding.to_string());
+            match coding {
+                "x-upper" => Ok(bytes.to_ascii_lowercase()),
+                "x-reverse" => Ok(bytes.into_iter().rev().collect()),
+                _ => Err(Error::InvalidHeader(String::from("Content-Encoding"))),
+            }
+        }).await.unwrap();
+        assert_eq!(length, 5);
+        assert_eq!(body.bytes(), b"hello");
+        assert_eq!(order, vec!["x-upper", "x-reverse"]);
+    }
+
+    #[cfg(feature = "digest")]
+    #[async_std::test]
+    async fn computes_digest_while_reading() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        let mut body = Body::new();
+        assert_eq!(body.digest(), None);
+        body.set_digest(true);
+        body.read(&mut "3\r\nabc\r\n0\r\n\r\n".as_bytes(), &headers).await.unwrap();
+        let expected: [u8; 32] = Sha256::digest(b"abc").into();
+        assert_eq!(body.digest(), Some(expected));
+    }
+
+    #[async_std::test]
+    async fn writes_from_reader() {
+        let mut output = Vec::new();
+        let mut file = "x".repeat(3000);
+        file.push_str("NEXT");
+        let mut reader = file.as_bytes();
+        assert_eq!(Body::write_from_reader(&mut output, &mut reader, 3000).await, Ok(3000));
+        assert_eq!(output.len(), 3000);
+        assert_eq!(reader, b"NEXT");
+        let res = Body::write_from_reader(&mut Vec::new(), &mut "abc".as_bytes(), 5).await;
+        assert_eq!(res, Err(Error::InvalidData));
+    }
+
+    #[async_std::test]
+    async fn displays_as_text() {
+        assert_eq!(Body::from("Hello").to_string(), "Hello");
+        assert_eq!(Body::from(vec![72, 105, 0xFF]).to_string(), "Hi\u{FFFD}");
+        assert!(format!("{:?}", Body::from("Hi")).contains("bytes: [72, 105]"));
+    }
+
+    #[async_std::test]
+    async fn appends_bytes() {
+        let mut body = Body::new();
+        body.set_length_limit(8);
+        body.append(b"Hello").unwrap();
+        body.append(b"!").unwrap();
+        assert_eq!(body.bytes(), b"Hello!");
+        assert_eq!(body.append(b"!!!"), Err(Error::SizeLimitExceeded(8)));
+        assert_eq!(body.length(), 6);
+    }
+
+    #[async_std::test]
+    async fn sends_continue_before_first_read() {
+        let mut req = Request::new();
+        req.set_method("POST");
+        req.set_header("Content-Length", "5");
+        req.set_header("Expect", "100-continue");
+        let mut output = Vec::new();
+        let mut body = Body::new();
+        body.read_request(&mut "Hello".as_bytes(), &req, || req.send_continue(&mut output)).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        assert_eq!(output, b"HTTP/1.1 100 Continue\r\n\r\n");
+
+        req.set_method("GET");
+        req.remove_header("Content-Length");
+        let mut output = Vec::new();
+        let length = Body::new().read_request(&mut "".as_bytes(), &req, || req.send_continue(&mut output)).await.unwrap();
+        assert_eq!(length, 0);
+        assert!(output.is_empty());
+    }
+
+    #[async_std::test]
+    async fn uses_primed_bytes_in_every_reader() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("5"));
+        let mut stream = "loNEXT".as_bytes();
+        let mut body = Body::new();
+        body.prime(b"Hel");
+        assert_eq!(body.drain(&mut stream, &headers).await, Ok(5));
+        assert_eq!(stream, b"NEXT");
+
+        let mut stream = "loNEXT".as_bytes();
+        let mut body = Body::new();
+        body.prime(b"Hel");
+        body.read_with_limit_callback(&mut stream, &headers, |_| ()).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        assert_eq!(stream, b"NEXT");
+
+        let mut stream = "loNEXT".as_bytes();
+        let mut body = Body::new();
+        body.prime(b"Hel");
+        body.read_sized(&mut stream, 5).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        assert_eq!(stream, b"NEXT");
+
+        let mut stream = "lo\r\n0\r\n\r\nNEXT".as_bytes();
+        let mut body = Body::new();
+        body.prime(b"5\r\nHel");
+        body.read_chunked(&mut stream).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        assert_eq!(stream, b"NEXT");
+    }
+
+    #[async_std::test]
+    async fn reads_empty_chunked_body_with_max_limit() {
+        let mut body = Body::new();
+        body.set_length_limit(usize::MAX);
+        assert_eq!(body.read_chunked(&mut "0\r\n\r\n".as_bytes()).await.unwrap(), 0);
+    }
+
+    #[async_std::test]
+    async fn reads_rest_after_primed_bytes() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("5"));
+        let mut body = Body::new();
+        body.prime(b"He");
+        body.read(&mut "lloGET".as_bytes(), &headers).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        assert!(body.primed().is_empty());
+
+        let mut body = Body::new();
+        body.prime(b"HelloGET");
+        let mut stream = "unread".as_bytes();
+        body.read(&mut stream, &headers).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        assert_eq!(body.primed(), b"GET");
+        assert_eq!(stream, b"unread");
+
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        let mut body = Body::new();
+        body.prime(b"5\r\nHel");
+        body.read(&mut "lo\r\n0\r\n\r\n".as_bytes(), &headers).await.unwrap();
+        assert_eq!(body.bytes(), b"Hello");
     }
 }
@@ -0,0 +1,244 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SameSite {
+    Strict,
+    Lax,
+    None,
+}
+
+impl fmt::Display for SameSite {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SameSite::Strict => write!(fmt, "Strict"),
+            SameSite::Lax => write!(fmt, "Lax"),
+            SameSite::None => write!(fmt, "None"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cookie {
+    name: String,
+    value: String,
+    domain: Option<String>,
+    path: Option<String>,
+    expires: Option<String>,
+    max_age: Option<i64>,
+    secure: bool,
+    http_only: bool,
+    same_site: Option<SameSite>,
+}
+
+impl Cookie {
+
+    pub fn new<N: Into<String>, V: Into<String>>(name: N, value: V) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+            domain: None,
+            path: None,
+            expires: None,
+            max_age: None,
+            secure: false,
+            http_only: false,
+            same_site: None,
+        }
+    }
+
+    pub fn name(&self) -> &String {
+        &self.name
+    }
+
+    pub fn value(&self) -> &String {
+        &self.value
+    }
+
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    pub fn expires(&self) -> Option<&str> {
+        self.expires.as_deref()
+    }
+
+    pub fn max_age(&self) -> Option<i64> {
+        self.max_age
+    }
+
+    pub fn is_secure(&self) -> bool {
+        self.secure
+    }
+
+    pub fn is_http_only(&self) -> bool {
+        self.http_only
+    }
+
+    pub fn same_site(&self) -> Option<&SameSite> {
+        self.same_site.as_ref()
+    }
+
+    pub fn has_domain(&self) -> bool {
+        self.domain.is_some()
+    }
+
+    pub fn has_path(&self) -> bool {
+        self.path.is_some()
+    }
+
+    pub fn has_expires(&self) -> bool {
+        self.expires.is_some()
+    }
+
+    pub fn has_max_age(&self) -> bool {
+        self.max_age.is_some()
+    }
+
+    pub fn has_same_site(&self) -> bool {
+        self.same_site.is_some()
+    }
+
+    pub fn set_name<V: Into<String>>(&mut self, value: V) {
+        self.name = value.into();
+    }
+
+    pub fn set_value<V: Into<String>>(&mut self, value: V) {
+        self.value = value.into();
+    }
+
+    pub fn set_domain<V: Into<String>>(&mut self, value: V) {
+        self.domain = Some(value.into());
+    }
+
+    pub fn set_path<V: Into<String>>(&mut self, value: V) {
+        self.path = Some(value.into());
+    }
+
+    pub fn set_expires<V: Into<String>>(&mut self, value: V) {
+        self.expires = Some(value.into());
+    }
+
+    pub fn set_max_age(&mut self, value: i64) {
+        self.max_age = Some(value);
+    }
+
+    pub fn set_secure(&mut self, value: bool) {
+        self.secure = value;
+    }
+
+    pub fn set_http_only(&mut self, value: bool) {
+        self.http_only = value;
+    }
+
+    pub fn set_same_site(&mut self, value: SameSite) {
+        self.same_site = Some(value);
+    }
+
+    pub fn remove_domain(&mut self) {
+        self.domain = None;
+    }
+
+    pub fn remove_path(&mut self) {
+        self.path = None;
+    }
+
+    pub fn remove_expires(&mut self) {
+        self.expires = None;
+    }
+
+    pub fn remove_max_age(&mut self) {
+        self.max_age = None;
+    }
+
+    pub fn remove_same_site(&mut self) {
+        self.same_site = None;
+    }
+
+    pub fn is_valid(&self) -> bool {
+        let value = match self.value.strip_prefix('"').and_then(|value| value.strip_suffix('"')) {
+            Some(value) => value, // quoted cookie value
+            None => &self.value,
+        };
+        let attributes = [&self.domain, &self.path, &self.expires];
+        !self.name.is_empty()
+            && self.name.bytes().all(|b| b.is_ascii_graphic() && !b"()<>@,;:\\\"/[]?={}".contains(&b))
+            && value.bytes().all(|b| b.is_ascii_graphic() && !b"\",;\\".contains(&b))
+            && attributes.iter().copied().flatten().all(|value| !value.bytes().any(|b| b.is_ascii_control() || b == b';'))
+    }
+}
+
+impl fmt::Display for Cookie {
+    fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
+        write!(fmt, "{}={}", self.name, self.value)?;
+        if let Some(domain) = &self.domain {
+            write!(fmt, "; Domain={}", domain)?;
+        }
+        if let Some(path) = &self.path {
+            write!(fmt, "; Path={}", path)?;
+        }
+        if let Some(expires) = &self.expires {
+            write!(fmt, "; Expires={}", expires)?;
+        }
+        if let Some(max_age) = self.max_age {
+            write!(fmt, "; Max-Age={}", max_age)?;
+        }
+        if self.secure {
+            write!(fmt, "; Secure")?;
+        }
+        if self.http_only {
+            write!(fmt, "; HttpOnly")?;
+        }
+        if let Some(same_site) = &self.same_site {
+            write!(fmt, "; SameSite={}", same_site)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn formats_attributes() {
+        let mut cookie = Cookie::new("id", "a3fWa");
+        assert_eq!(cookie.to_string(), "id=a3fWa");
+        cookie.set_domain("a.com");
+        cookie.set_path("/");
+        cookie.set_expires("Wed, 21 Oct 2015 07:28:00 GMT");
+        cookie.set_max_age(3600);
+        cookie.set_secure(true);
+        cookie.set_http_only(true);
+        cookie.set_same_site(SameSite::Strict);
+        assert_eq!(cookie.to_string(), "id=a3fWa; Domain=a.com; Path=/; Expires=Wed, 21 Oct 2015 07:28:00 GMT; Max-Age=3600; Secure; HttpOnly; SameSite=Strict");
+        cookie.remove_domain();
+        cookie.remove_expires();
+        cookie.remove_max_age();
+        cookie.remove_same_site();
+        cookie.set_http_only(false);
+        assert_eq!(cookie.to_string(), "id=a3fWa; Path=/; Secure");
+    }
+
+    #[async_std::test]
+    async fn validates_cookie() {
+        assert!(Cookie::new("id", "1").is_valid());
+        assert!(!Cookie::new("id", "\"a b\"").is_valid());
+        assert!(Cookie::new("id", "\"ab\"").is_valid());
+        assert!(!Cookie::new("", "1").is_valid());
+        assert!(!Cookie::new("i d", "1").is_valid());
+        assert!(!Cookie::new("id;", "1").is_valid());
+        assert!(!Cookie::new("id", "1; Secure").is_valid());
+        assert!(!Cookie::new("id", "1\r\nX-Injected: 1").is_valid());
+        let mut cookie = Cookie::new("id", "1");
+        cookie.set_path("/\r\nX-Injected: 1");
+        assert!(!cookie.is_valid());
+        cookie.set_path("/; Domain=evil.com");
+        assert!(!cookie.is_valid());
+        cookie.set_path("/a b");
+        assert!(cookie.is_valid());
+    }
+}
@@ -1,9 +1,17 @@
 #[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
 pub enum Error {
     StreamNotReadable,
     StreamNotWritable,
+    PartialWrite(usize),
+    Timeout,
     SizeLimitExceeded(usize),
+    TooManyChunks(usize),
     InvalidData,
+    UriTooLong(usize),
+    ParseError { offset: usize, context: String },
+    UnsupportedVersion(String),
     InvalidHeader(String),
     MissingHeader(String),
+    TooManyRedirects(usize),
 }
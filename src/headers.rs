@@ -0,0 +1,122 @@
+use std::slice;
+use std::collections::hash_map::Iter;
+use crate::{Error, parse_content_length};
+
+pub struct HeadersIter<'a> {
+    inner: Iter<'a, String, String>,
+    repeated: slice::Iter<'a, (String, String)>,
+}
+
+impl<'a> HeadersIter<'a> {
+
+    pub fn new(inner: Iter<'a, String, String>) -> Self {
+        Self::with_repeated(inner, &[])
+    }
+
+    pub(crate) fn with_repeated(inner: Iter<'a, String, String>, repeated: &'a [(String, String)]) -> Self {
+        Self {
+            inner,
+            repeated: repeated.iter(),
+        }
+    }
+}
+
+impl<'a> Iterator for HeadersIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner.next()
+            .or_else(|| self.repeated.next().map(|(name, value)| (name, value))) // field lines that are never folded
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let size = self.inner.len() + self.repeated.len();
+        (size, Some(size))
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KnownHeaders {
+    host: Option<String>,
+    content_length: Option<u64>,
+    content_type: Option<String>,
+    connection: Option<String>,
+    transfer_encoding: Option<String>,
+    user_agent: Option<String>,
+}
+
+impl KnownHeaders {
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn host(&self) -> Option<&str> {
+        self.host.as_deref()
+    }
+
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    pub fn content_type(&self) -> Option<&str> {
+        self.content_type.as_deref()
+    }
+
+    pub fn connection(&self) -> Option<&str> {
+        self.connection.as_deref()
+    }
+
+    pub fn transfer_encoding(&self) -> Option<&str> {
+        self.transfer_encoding.as_deref()
+    }
+
+    pub fn user_agent(&self) -> Option<&str> {
+        self.user_agent.as_deref()
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    pub(crate) fn accept(&mut self, name: String, value: String) -> Result<Option<(String, String)>, Error> {
+        let (field, canonical) = match name.len() { // cheap pre-filter before comparing names
+            4 if name.eq_ignore_ascii_case("Host") => (&mut self.host, "Host"),
+            10 if name.eq_ignore_ascii_case("Connection") => {
+                append_list(&mut self.connection, &value);
+                return Ok(None);
+            },
+            10 if name.eq_ignore_ascii_case("User-Agent") => (&mut self.user_agent, "User-Agent"),
+            12 if name.eq_ignore_ascii_case("Content-Type") => (&mut self.content_type, "Content-Type"),
+            14 if name.eq_ignore_ascii_case("Content-Length") => { // framing headers stay in the map for body readers
+                let length = parse_content_length(&value)?;
+                return match self.content_length.replace(length) {
+                    Some(current) if current != length => Err(Error::InvalidHeader(String::from("Content-Length"))),
+                    Some(_) => Ok(None), // an identical repeat adds nothing to the map
+                    None => Ok(Some((name, value))),
+                };
+            },
+            17 if name.eq_ignore_ascii_case("Transfer-Encoding") => {
+                append_list(&mut self.transfer_encoding, &value); // merged like the map value
+                return Ok(Some((name, value)));
+            },
+            _ => return Ok(Some((name, value))),
+        };
+        if field.is_some() { // a second line would disagree with the typed field
+            return Err(Error::InvalidHeader(String::from(canonical)));
+        }
+        *field = Some(value);
+        Ok(None)
+    }
+}
+
+fn append_list(field: &mut Option<String>, value: &str) {
+    match field {
+        Some(current) => {
+            current.push_str(", ");
+            current.push_str(value);
+        },
+        None => *field = Some(value.to_string()),
+    };
+}
@@ -1,13 +1,35 @@
+mod auth;
 mod body;
+mod cookie;
 mod errors;
+mod headers;
+mod method;
+mod parser;
 mod request;
 mod response;
+mod range;
+mod rate;
+mod reader;
+mod redirect;
 mod relay;
+mod retry;
+mod smuggling;
 mod utils;
 
+pub use auth::*;
 pub use body::*;
+pub use cookie::*;
 pub use errors::*;
+pub use headers::*;
+pub use method::*;
+pub use parser::*;
 pub use request::*;
 pub use response::*;
+pub use range::*;
+pub use rate::*;
+pub use reader::*;
+pub use redirect::*;
 pub use relay::*;
+pub use retry::*;
+pub use smuggling::*;
 pub use utils::*;
@@ -0,0 +1,215 @@
+use async_std::prelude::*;
+use async_std::io::{Read};
+use crate::{Error, Request, ReaderConfig, DEFAULT_MAX_HEAD_SIZE};
+use crate::utils::{head_overflow, read_error};
+
+#[derive(Debug)]
+pub struct RequestParser {
+    buffer: Vec<u8>,
+    config: ReaderConfig,
+    line_start: usize,
+    lines: usize,
+    empty_lines: usize,
+    complete: bool,
+}
+
+impl RequestParser {
+
+    pub fn new() -> Self {
+        Self::with_config(ReaderConfig::new())
+    }
+
+    pub fn with_config(config: ReaderConfig) -> Self {
+        Self {
+            buffer: Vec::new(),
+            config,
+            line_start: 0,
+            lines: 0,
+            empty_lines: 0,
+            complete: false,
+        }
+    }
+
+    pub fn buffer(&self) -> &[u8] {
+        &self.buffer
+    }
+
+    pub fn config(&self) -> &ReaderConfig {
+        &self.config
+    }
+
+    pub fn config_mut(&mut self) -> &mut ReaderConfig {
+        &mut self.config
+    }
+
+    pub async fn read<I>(&mut self, stream: &mut I) -> Result<Request, Error>
+        where
+        I: Read + Unpin,
+    {
+        if self.buffer.is_empty() { // a new message gets a fresh budget
+            self.config.reset_counters();
+        }
+
+        while !self.complete {
+            if let Some(limit) = self.config.remaining() {
+                if self.buffer.len() >= limit {
+                    return Err(Error::SizeLimitExceeded(self.config.length_limit().unwrap_or(limit)));
+                }
+            }
+            if self.lines == 0 && self.buffer.len() - self.line_start >= DEFAULT_MAX_HEAD_SIZE { // method + url + version
+                return Err(head_overflow(&self.buffer[self.line_start..], &self.config));
+            }
+
+            let mut bytes = [0u8];
+            let size = match stream.read(&mut bytes).await {
+                Ok(size) => size,
+                Err(err) => return Err(read_error(err)),
+            };
+            if size == 0 {
+                return Err(Error::StreamNotReadable);
+            }
+            self.push(bytes[0]); // kept across cancellations
+        }
+
+        let buffer = std::mem::take(&mut self.buffer);
+        let result = Request::read_with(&mut buffer.as_slice(), &mut self.config).await;
+        self.reset(); // the next message starts from scratch, whether this one parsed or not
+        result
+    }
+
+    pub fn clear(&mut self) {
+        self.config.clear();
+        self.reset();
+    }
+
+    fn reset(&mut self) {
+        self.buffer.clear();
+        self.line_start = 0;
+        self.lines = 0;
+        self.empty_lines = 0;
+        self.complete = false;
+    }
+
+    fn push(&mut self, byte: u8) {
+        self.buffer.push(byte);
+        if byte != 10 { // \n
+            return;
+        }
+
+        let mut line = &self.buffer[self.line_start..self.buffer.len() - 1];
+        if line.last() == Some(&13) {
+            line = &line[0..line.len() - 1];
+        }
+        self.line_start = self.buffer.len();
+
+        if line.is_empty() && self.lines > 0 {
+            self.complete = true;
+        } else if line.is_empty() { // leading empty lines count against the same limit the reader applies
+            self.empty_lines += 1;
+            self.complete = match self.config.empty_lines_limit() {
+                Some(limit) => self.empty_lines > limit,
+                None => true, // rejected by the reader
+            };
+        } else {
+            self.lines += 1;
+            if self.lines == 1 && line.split(|b| *b == 32).filter(|part| !part.is_empty()).count() < 3 {
+                self.complete = true; // HTTP/0.9
+            }
+        }
+    }
+}
+
+impl Default for RequestParser {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+    use std::time::Duration;
+
+    struct PausedStream {
+        data: Vec<u8>,
+        position: usize,
+        pause: Option<usize>,
+    }
+
+    impl Read for PausedStream {
+        fn poll_read(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+            if Some(self.position) == self.pause {
+                return Poll::Pending;
+            }
+            let size = std::cmp::min(buf.len(), self.data.len() - self.position);
+            buf[0..size].copy_from_slice(&self.data[self.position..self.position + size]);
+            self.position += size;
+            Poll::Ready(Ok(size))
+        }
+    }
+
+    #[async_std::test]
+    async fn resumes_after_cancellation() {
+        let data = b"GET /path HTTP/1.1\r\nHost: a.com\r\n\r\nBODY".to_vec();
+        let mut stream = PausedStream { data, position: 0, pause: Some(25) };
+        let mut parser = RequestParser::new();
+        let res = async_std::future::timeout(Duration::from_millis(10), parser.read(&mut stream)).await;
+        assert!(res.is_err());
+        assert_eq!(parser.buffer(), b"GET /path HTTP/1.1\r\nHost:");
+
+        stream.pause = None;
+        let req = parser.read(&mut stream).await.unwrap();
+        assert_eq!(req.uri(), "/path");
+        assert_eq!(req.header("Host").unwrap(), "a.com");
+        assert_eq!(&stream.data[stream.position..], b"BODY");
+    }
+
+    #[async_std::test]
+    async fn reads_simple_request() {
+        let mut parser = RequestParser::new();
+        let req = parser.read(&mut "GET /\r\n".as_bytes()).await.unwrap();
+        assert!(req.has_version("HTTP/0.9"));
+    }
+
+    #[async_std::test]
+    async fn caps_buffered_request_line() {
+        let data = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(100_000));
+        let mut parser = RequestParser::new();
+        let res = parser.read(&mut data.as_bytes()).await;
+        assert_eq!(res.unwrap_err(), Error::UriTooLong(DEFAULT_MAX_HEAD_SIZE));
+        assert!(parser.buffer().len() <= DEFAULT_MAX_HEAD_SIZE);
+        let mut parser = RequestParser::new();
+        parser.config_mut().set_uri_length_limit(10);
+        let res = parser.read(&mut format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(20)).as_bytes()).await;
+        assert_eq!(res.unwrap_err(), Error::UriTooLong(10));
+    }
+
+    #[async_std::test]
+    async fn resets_limit_between_requests() {
+        let mut parser = RequestParser::new();
+        parser.config_mut().set_length_limit(30);
+        let mut stream = "GET / HTTP/1.1\r\nA: 1\r\n\r\nGET / HTTP/1.1\r\nB: 2\r\n\r\n".as_bytes();
+        assert_eq!(parser.read(&mut stream).await.unwrap().header("A").unwrap(), "1");
+        assert_eq!(parser.read(&mut stream).await.unwrap().header("B").unwrap(), "2");
+        assert_eq!(parser.config().header_count(), 1);
+    }
+
+    #[async_std::test]
+    async fn limits_leading_empty_lines() {
+        let data = "\r\n".repeat(100_000);
+        let mut parser = RequestParser::new();
+        assert_eq!(parser.read(&mut data.as_bytes()).await.unwrap_err(), Error::InvalidData);
+        assert!(parser.buffer().is_empty());
+
+        let mut parser = RequestParser::new();
+        parser.config_mut().set_empty_lines_limit(2);
+        let mut stream = "\r\n\r\nGET / HTTP/1.1\r\n\r\n\r\n\r\n\r\nGET / HTTP/1.1\r\n\r\n".as_bytes();
+        assert_eq!(parser.read(&mut stream).await.unwrap().uri(), "/");
+        assert_eq!(parser.read(&mut stream).await.unwrap_err(), Error::InvalidData);
+        assert!(parser.buffer().is_empty());
+        assert_eq!(stream, b"GET / HTTP/1.1\r\n\r\n");
+        assert_eq!(parser.read(&mut stream).await.unwrap().uri(), "/");
+    }
+}
@@ -0,0 +1,66 @@
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ContentRange {
+    Bytes { start: u64, end: u64, total: Option<u64> },
+    Unsatisfied(u64),
+}
+
+impl ContentRange {
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        let (unit, rest) = value.split_once(' ')?;
+        if !unit.eq_ignore_ascii_case("bytes") {
+            return None;
+        }
+        let (range, total) = rest.trim().split_once('/')?;
+        let total = match total {
+            "*" => None,
+            total => Some(parse_number(total)?),
+        };
+
+        if range == "*" {
+            return total.map(ContentRange::Unsatisfied);
+        }
+        let (start, end) = range.split_once('-')?;
+        let (start, end) = (parse_number(start)?, parse_number(end)?);
+        if start > end || end == u64::MAX || total.is_some_and(|total| end >= total) { // length must fit in u64
+            return None;
+        }
+        Some(ContentRange::Bytes { start, end, total })
+    }
+
+    pub fn length(&self) -> Option<u64> {
+        match self {
+            ContentRange::Bytes { start, end, .. } => end.checked_sub(*start)?.checked_add(1), // variants can be built by hand
+            ContentRange::Unsatisfied(_) => None,
+        }
+    }
+}
+
+fn parse_number(value: &str) -> Option<u64> {
+    match !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+        true => value.parse::<u64>().ok(),
+        false => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn parses_content_range() {
+        assert_eq!(ContentRange::parse("bytes 0-99/1000"), Some(ContentRange::Bytes { start: 0, end: 99, total: Some(1000) }));
+        assert_eq!(ContentRange::parse("bytes 100-199/*"), Some(ContentRange::Bytes { start: 100, end: 199, total: None }));
+        assert_eq!(ContentRange::parse("bytes */1000"), Some(ContentRange::Unsatisfied(1000)));
+        assert_eq!(ContentRange::parse("bytes 0-99/1000").unwrap().length(), Some(100));
+        assert_eq!(ContentRange::parse("bytes 99-0/1000"), None);
+        assert_eq!(ContentRange::parse("bytes 0-1000/1000"), None);
+        assert_eq!(ContentRange::parse("bytes */*"), None);
+        assert_eq!(ContentRange::parse("items 0-1/2"), None);
+        assert_eq!(ContentRange::parse("bytes -1-5/10"), None);
+        assert_eq!(ContentRange::parse("bytes 0-18446744073709551615/*"), None);
+        assert_eq!(ContentRange::parse("bytes 0-18446744073709551614/*").unwrap().length(), Some(u64::MAX));
+        assert_eq!(ContentRange::Bytes { start: 0, end: u64::MAX, total: None }.length(), None);
+    }
+}
@@ -0,0 +1,132 @@
+use std::pin::Pin;
+use std::future::Future;
+use std::time::Duration;
+use std::task::{Context, Poll};
+use async_std::io::{self, Read, Write};
+use async_std::task;
+
+pub struct MinRateStream<'a, S> {
+    stream: &'a mut S,
+    rate: Option<(usize, Duration)>,
+    received: usize,
+    timer: Option<Pin<Box<dyn Future<Output = ()> + Send>>>,
+}
+
+impl<'a, S> MinRateStream<'a, S> {
+
+    pub fn new(stream: &'a mut S, rate: Option<(usize, Duration)>) -> Self {
+        Self {
+            stream,
+            rate,
+            received: 0,
+            timer: None,
+        }
+    }
+}
+
+impl<'a, S: Read + Unpin> Read for MinRateStream<'a, S> {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = &mut *self;
+
+        if let Some((bytes, interval)) = this.rate {
+            let timer = this.timer.get_or_insert_with(|| Box::pin(task::sleep(interval)));
+            if timer.as_mut().poll(cx).is_ready() { // at most one deadline per poll, so a zero interval cannot spin
+                if this.received < bytes {
+                    this.timer = None;
+                    return Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "read rate too low")));
+                }
+                this.received = 0; // next interval
+                let mut timer = Box::pin(task::sleep(interval));
+                this.timer = match timer.as_mut().poll(cx) { // registers the waker for the next deadline
+                    Poll::Pending => Some(timer),
+                    Poll::Ready(()) => None,
+                };
+            }
+        }
+
+        let poll = Pin::new(&mut *this.stream).poll_read(cx, buf); // after the deadline check so read bytes are never dropped
+        if let Poll::Ready(Ok(size)) = poll {
+            this.received += size;
+        }
+        poll
+    }
+}
+
+impl<'a, S: Write + Unpin> Write for MinRateStream<'a, S> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        Pin::new(&mut *self.stream).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.stream).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut *self.stream).poll_close(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::prelude::*;
+
+    struct StalledStream;
+
+    impl Read for StalledStream {
+        fn poll_read(self: Pin<&mut Self>, _: &mut Context<'_>, _: &mut [u8]) -> Poll<io::Result<usize>> {
+            Poll::Pending
+        }
+    }
+
+    #[async_std::test]
+    async fn fails_on_stalled_stream() {
+        let mut stream = StalledStream;
+        let mut stream = MinRateStream::new(&mut stream, Some((1, Duration::from_millis(10))));
+        let err = stream.read(&mut [0u8; 4]).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    }
+
+    struct SlowStream {
+        reads: usize,
+    }
+
+    impl Read for SlowStream {
+        fn poll_read(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+            std::thread::sleep(Duration::from_millis(20));
+            self.reads += 1;
+            buf[0] = b'a';
+            Poll::Ready(Ok(1))
+        }
+    }
+
+    #[async_std::test]
+    async fn fails_before_reading_past_deadline() {
+        let mut inner = SlowStream { reads: 0 };
+        let mut stream = MinRateStream::new(&mut inner, Some((100, Duration::from_millis(10))));
+        let mut buf = [0u8; 4];
+        assert_eq!(stream.read(&mut buf).await.unwrap(), 1);
+        let err = stream.read(&mut buf).await.unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert_eq!(inner.reads, 1);
+    }
+
+    #[async_std::test]
+    async fn does_not_spin_on_zero_interval() {
+        let mut stream = "Hello".as_bytes();
+        let mut stream = MinRateStream::new(&mut stream, Some((0, Duration::ZERO)));
+        let mut output = String::new();
+        let res = async_std::future::timeout(Duration::from_secs(3), stream.read_to_string(&mut output)).await;
+        assert_eq!(res.unwrap().unwrap(), 5);
+        assert_eq!(output, "Hello");
+    }
+
+    #[async_std::test]
+    async fn passes_through_fast_stream() {
+        let mut stream = "Hello".as_bytes();
+        let mut stream = MinRateStream::new(&mut stream, Some((1, Duration::from_millis(10))));
+        let mut output = String::new();
+        stream.read_to_string(&mut output).await.unwrap();
+        assert_eq!(output, "Hello");
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{Error, DEFAULT_HEADERS_CAPACITY};
+
+#[derive(Debug)]
+pub struct ReaderConfig {
+    length: usize,
+    length_limit: Option<usize>,
+    header_value_limit: Option<usize>,
+    uri_length_limit: Option<usize>,
+    empty_lines_limit: Option<usize>,
+    lenient: bool,
+    headers_capacity: usize,
+    header_count: usize,
+}
+
+impl ReaderConfig {
+
+    pub fn new() -> Self {
+        Self {
+            length: 0,
+            length_limit: None,
+            header_value_limit: None,
+            uri_length_limit: None,
+            empty_lines_limit: None,
+            lenient: false,
+            headers_capacity: DEFAULT_HEADERS_CAPACITY,
+            header_count: 0,
+        }
+    }
+
+    pub fn length(&self) -> usize {
+        self.length
+    }
+
+    pub fn length_limit(&self) -> Option<usize> {
+        self.length_limit
+    }
+
+    pub fn has_length_limit(&self) -> bool {
+        self.length_limit.is_some()
+    }
+
+    pub fn set_length_limit(&mut self, limit: usize) {
+        self.length_limit = Some(limit);
+    }
+
+    pub fn remove_length_limit(&mut self) {
+        self.length_limit = None;
+    }
+
+    pub fn header_value_limit(&self) -> Option<usize> {
+        self.header_value_limit
+    }
+
+    pub fn has_header_value_limit(&self) -> bool {
+        self.header_value_limit.is_some()
+    }
+
+    pub fn set_header_value_limit(&mut self, limit: usize) {
+        self.header_value_limit = Some(limit);
+    }
+
+    pub fn remove_header_value_limit(&mut self) {
+        self.header_value_limit = None;
+    }
+
+    pub fn uri_length_limit(&self) -> Option<usize> {
+        self.uri_length_limit
+    }
+
+    pub fn has_uri_length_limit(&self) -> bool {
+        self.uri_length_limit.is_some()
+    }
+
+    pub fn set_uri_length_limit(&mut self, limit: usize) {
+        self.uri_length_limit = Some(limit);
+    }
+
+    pub fn remove_uri_length_limit(&mut self) {
+        self.uri_length_limit = None;
+    }
+
+    pub fn empty_lines_limit(&self) -> Option<usize> {
+        self.empty_lines_limit
+    }
+
+    pub fn has_empty_lines_limit(&self) -> bool {
+        self.empty_lines_limit.is_some()
+    }
+
+    pub fn set_empty_lines_limit(&mut self, limit: usize) {
+        self.empty_lines_limit = Some(limit);
+    }
+
+    pub fn remove_empty_lines_limit(&mut self) {
+        self.empty_lines_limit = None;
+    }
+
+    pub fn is_lenient(&self) -> bool {
+        self.lenient
+    }
+
+    pub fn set_lenient(&mut self, value: bool) {
+        self.lenient = value;
+    }
+
+    pub fn headers_capacity(&self) -> usize {
+        self.headers_capacity
+    }
+
+    pub fn set_headers_capacity(&mut self, capacity: usize) {
+        self.headers_capacity = capacity;
+    }
+
+    pub fn header_count(&self) -> usize {
+        self.header_count
+    }
+
+    pub(crate) fn count_header(&mut self) {
+        self.header_count += 1;
+    }
+
+    pub(crate) fn reset_header_count(&mut self) {
+        self.header_count = 0;
+    }
+
+    pub fn reset_counters(&mut self) {
+        self.length = 0;
+        self.header_count = 0;
+    }
+
+    pub(crate) fn remaining(&self) -> Option<usize> {
+        self.length_limit.map(|limit| limit.saturating_sub(self.length))
+    }
+
+    pub(crate) fn consume(&mut self, length: usize) -> Result<(), Error> {
+        self.length += length;
+        match self.length_limit {
+            Some(limit) if limit < self.length => Err(Error::SizeLimitExceeded(limit)),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.length = 0;
+        self.length_limit = None;
+        self.header_value_limit = None;
+        self.uri_length_limit = None;
+        self.empty_lines_limit = None;
+        self.lenient = false;
+        self.headers_capacity = DEFAULT_HEADERS_CAPACITY;
+        self.header_count = 0;
+    }
+}
+
+impl Default for ReaderConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Body, Request, DEFAULT_MAX_HEAD_SIZE};
+
+    #[async_std::test]
+    async fn limits_total_request_size() {
+        let data = "POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\n0123456789";
+        let mut config = ReaderConfig::new();
+        config.set_length_limit(49);
+        let mut stream = data.as_bytes();
+        let req = Request::read_with(&mut stream, &mut config).await.unwrap();
+        Body::new().read_with(&mut stream, req.headers(), &mut config).await.unwrap();
+        assert_eq!(config.length(), 49);
+
+        let mut config = ReaderConfig::new();
+        config.set_length_limit(41);
+        let mut stream = data.as_bytes();
+        let req = Request::read_with(&mut stream, &mut config).await.unwrap();
+        let mut body = Body::new();
+        body.set_length_limit(100);
+        let res = body.read_with(&mut stream, req.headers(), &mut config).await;
+        assert_eq!(res, Err(Error::SizeLimitExceeded(41)));
+        assert_eq!(body.length_limit(), Some(100));
+    }
+
+    #[async_std::test]
+    async fn tells_value_limit_from_total_limit() {
+        let data = "GET / HTTP/1.1\r\nn: 1111\r\n\r\n";
+        let mut config = ReaderConfig::new();
+        config.set_length_limit(20);
+        config.set_header_value_limit(4);
+        let res = Request::read_with(&mut data.as_bytes(), &mut config).await;
+        assert_eq!(res.unwrap_err(), Error::SizeLimitExceeded(20));
+
+        let mut config = ReaderConfig::new();
+        config.set_length_limit(100);
+        config.set_header_value_limit(3);
+        let res = Request::read_with(&mut data.as_bytes(), &mut config).await;
+        assert_eq!(res.unwrap_err(), Error::SizeLimitExceeded(3));
+
+        let mut config = ReaderConfig::new();
+        config.set_header_value_limit(usize::MAX);
+        let req = Request::read_with(&mut "GET / HTTP/1.1\r\nHost: a\r\n\r\n".as_bytes(), &mut config).await.unwrap();
+        assert_eq!(req.header("Host").unwrap(), "a");
+    }
+
+    #[async_std::test]
+    async fn clears_every_field() {
+        let mut config = ReaderConfig::new();
+        config.set_length_limit(1);
+        config.set_header_value_limit(2);
+        config.set_uri_length_limit(3);
+        config.set_empty_lines_limit(4);
+        config.set_lenient(true);
+        config.set_headers_capacity(5);
+        config.consume(1).unwrap();
+        config.count_header();
+        config.remove_header_value_limit();
+        assert_eq!(config.uri_length_limit(), Some(3));
+        assert_eq!(config.empty_lines_limit(), Some(4));
+
+        config.clear();
+        assert_eq!(format!("{:?}", config), format!("{:?}", ReaderConfig::new()));
+    }
+
+    #[async_std::test]
+    async fn skips_leading_empty_lines() {
+        let data = "\r\n\r\nGET / HTTP/1.1\r\n\r\n";
+        let mut config = ReaderConfig::new();
+        assert!(Request::read_with(&mut data.as_bytes(), &mut config).await.is_err());
+
+        let mut config = ReaderConfig::new();
+        config.set_empty_lines_limit(2);
+        let req = Request::read_with(&mut data.as_bytes(), &mut config).await.unwrap();
+        assert_eq!(req.request_line(), "GET / HTTP/1.1");
+        assert_eq!(config.length(), data.len());
+
+        let mut config = ReaderConfig::new();
+        config.set_empty_lines_limit(1);
+        let res = Request::read_with(&mut data.as_bytes(), &mut config).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+    }
+
+    #[async_std::test]
+    async fn reports_uri_over_start_line_cap() {
+        let data = format!("GET /{} HTTP/1.1\r\n\r\n", "a".repeat(300));
+        let res = Request::read_with(&mut data.as_bytes(), &mut ReaderConfig::new()).await;
+        assert_eq!(res.unwrap_err(), Error::UriTooLong(DEFAULT_MAX_HEAD_SIZE));
+        let mut config = ReaderConfig::new();
+        config.set_uri_length_limit(100);
+        let res = Request::read_with(&mut data.as_bytes(), &mut config).await;
+        assert_eq!(res.unwrap_err(), Error::UriTooLong(100));
+
+        let data = format!("GET / HTTP/1.1{}\r\n\r\n", "1".repeat(300));
+        let res = Request::read_with(&mut data.as_bytes(), &mut ReaderConfig::new()).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+    }
+
+    #[async_std::test]
+    async fn limits_uri_length() {
+        let mut config = ReaderConfig::new();
+        config.set_uri_length_limit(5);
+        assert!(Request::read_with(&mut "GET /abcd HTTP/1.1\r\n\r\n".as_bytes(), &mut config).await.is_ok());
+        let mut config = ReaderConfig::new();
+        config.set_uri_length_limit(5);
+        let res = Request::read_with(&mut "GET /abcde HTTP/1.1\r\n\r\n".as_bytes(), &mut config).await;
+        assert_eq!(res.unwrap_err(), Error::UriTooLong(5));
+    }
+}
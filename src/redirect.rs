@@ -0,0 +1,168 @@
+use crate::{Error, Request, Response, resolve_url};
+
+#[derive(Debug)]
+pub struct Redirects {
+    statuses: Vec<usize>,
+    hops_limit: Option<usize>,
+}
+
+impl Redirects {
+
+    pub fn new() -> Self {
+        Self {
+            statuses: Vec::new(),
+            hops_limit: None,
+        }
+    }
+
+    pub fn hops(&self) -> usize {
+        self.statuses.len()
+    }
+
+    pub fn statuses(&self) -> &Vec<usize> {
+        &self.statuses
+    }
+
+    pub fn hops_limit(&self) -> Option<usize> {
+        self.hops_limit
+    }
+
+    pub fn has_hops_limit(&self) -> bool {
+        self.hops_limit.is_some()
+    }
+
+    pub fn set_hops_limit(&mut self, limit: usize) {
+        self.hops_limit = Some(limit);
+    }
+
+    pub fn remove_hops_limit(&mut self) {
+        self.hops_limit = None;
+    }
+
+    pub fn follow(&mut self, req: &mut Request, res: &Response) -> Result<bool, Error> {
+        let status = res.status_code();
+        let location = match res.location() {
+            Some(location) if [301, 302, 303, 307, 308].contains(&status) => location,
+            _ => return Ok(false),
+        };
+
+        if let Some(limit) = self.hops_limit {
+            if self.statuses.len() >= limit {
+                return Err(Error::TooManyRedirects(limit));
+            }
+        }
+        self.statuses.push(status);
+
+        let uri = resolve_url(req.uri(), &location);
+        if let Some((scheme, host)) = origin(&uri) {
+            let same_origin = match origin(req.uri()) {
+                Some((from_scheme, from_host)) => from_scheme.eq_ignore_ascii_case(scheme) && from_host.eq_ignore_ascii_case(host),
+                None => req.header("Host").is_some_and(|from_host| from_host.eq_ignore_ascii_case(host)),
+            };
+            if !same_origin { // credentials are never sent to another origin
+                remove_headers(req, &["Authorization", "Proxy-Authorization", "Cookie"]);
+            }
+            remove_headers(req, &["Host"]);
+            req.set_header("Host", host);
+        }
+        req.set_uri(uri);
+        if status == 303 || (status <= 302 && req.has_method("POST")) {
+            req.set_method("GET");
+            remove_headers(req, &["Content-Length", "Transfer-Encoding", "Content-Type", "Content-Encoding", "Content-Language", "Content-Location"]);
+        }
+
+        Ok(true)
+    }
+
+    pub fn clear(&mut self) {
+        self.statuses.clear();
+        self.hops_limit = None;
+    }
+}
+
+fn origin(uri: &str) -> Option<(&str, &str)> { // scheme and host[:port] of an absolute URI
+    let index = uri.find("://")?;
+    let rest = &uri[index + 3..];
+    let authority = &rest[0..rest.find(['/', '?', '#']).unwrap_or(rest.len())];
+    Some((&uri[0..index], authority.rsplit('@').next().unwrap_or(authority)))
+}
+
+fn remove_headers(req: &mut Request, names: &[&str]) {
+    let found: Vec<String> = req.headers().keys()
+        .filter(|name| names.iter().any(|target| name.eq_ignore_ascii_case(target)))
+        .cloned()
+        .collect();
+    for name in found {
+        req.remove_header(name);
+    }
+}
+
+impl Default for Redirects {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[async_std::test]
+    async fn follows_redirects() {
+        let mut redirects = Redirects::new();
+        redirects.set_hops_limit(1);
+        let mut req = Request::new();
+        req.set_uri("/prev");
+        req.set_method("POST");
+        req.set_header("Content-Length", "5");
+        let mut res = Response::new();
+        assert_eq!(redirects.follow(&mut req, &res), Ok(false));
+        res.set_status_code(303);
+        res.set_header("Location", "next");
+        assert_eq!(redirects.follow(&mut req, &res), Ok(true));
+        assert_eq!(req.method(), "GET");
+        assert_eq!(req.uri(), "/next");
+        assert!(!req.has_header("Content-Length"));
+        assert_eq!(redirects.statuses(), &vec![303]);
+        assert_eq!(redirects.follow(&mut req, &res), Err(Error::TooManyRedirects(1)));
+    }
+
+    #[async_std::test]
+    async fn drops_credentials_across_origins() {
+        let mut redirects = Redirects::new();
+        let mut req = Request::new();
+        req.set_uri("/a");
+        req.set_header("Host", "a.com");
+        req.set_header("authorization", "Bearer secret");
+        req.set_header("Cookie", "id=1");
+        let mut res = Response::new();
+        res.set_status_code(307);
+        res.set_header("Location", "http://a.com/b");
+        assert_eq!(redirects.follow(&mut req, &res), Ok(true));
+        assert_eq!(req.header("Host").unwrap(), "a.com");
+        assert_eq!(req.header("authorization").unwrap(), "Bearer secret");
+
+        res.set_header("Location", "http://evil.com/x");
+        assert_eq!(redirects.follow(&mut req, &res), Ok(true));
+        assert_eq!(req.uri(), "http://evil.com/x");
+        assert_eq!(req.header("Host").unwrap(), "evil.com");
+        assert!(!req.has_header("authorization"));
+        assert!(!req.has_header("Cookie"));
+    }
+
+    #[async_std::test]
+    async fn drops_body_headers_when_switching_to_get() {
+        let mut redirects = Redirects::new();
+        let mut req = Request::new();
+        req.set_uri("/form");
+        req.set_method("POST");
+        req.set_header("Content-Type", "application/x-www-form-urlencoded");
+        req.set_header("content-length", "5");
+        let mut res = Response::new();
+        res.set_status_code(302);
+        res.set_header("Location", "/done");
+        assert_eq!(redirects.follow(&mut req, &res), Ok(true));
+        assert_eq!(req.method(), "GET");
+        assert!(req.headers().is_empty());
+    }
+}
@@ -1,11 +1,15 @@
+use std::convert::TryFrom;
+use std::time::Duration;
 use std::collections::HashMap;
 use async_std::io::{Read, Write};
-use crate::{Error, relay_chunked_stream, relay_sized_stream};
+use crate::{Error, Request, Response, MinRateStream, body_expected, is_chunked, parse_content_length, remove_hop_by_hop_headers, relay_chunked_stream, relay_sized_stream};
 
 #[derive(Debug)]
 pub struct Relay {
     length: usize,
     length_limit: Option<usize>,
+    min_read_rate: Option<(usize, Duration)>,
+    flush_interval: Option<usize>,
 }
 
 impl Relay {
@@ -14,6 +18,8 @@ impl Relay {
         Self {
             length: 0,
             length_limit: None,
+            min_read_rate: None,
+            flush_interval: None,
         }
     }
 
@@ -37,21 +43,67 @@ impl Relay {
         self.length_limit = None;
     }
 
+    pub fn min_read_rate(&self) -> Option<(usize, Duration)> {
+        self.min_read_rate
+    }
+
+    pub fn has_min_read_rate(&self) -> bool {
+        self.min_read_rate.is_some()
+    }
+
+    pub fn set_min_read_rate(&mut self, bytes: usize, interval: Duration) {
+        self.min_read_rate = Some((bytes, interval));
+    }
+
+    pub fn remove_min_read_rate(&mut self) {
+        self.min_read_rate = None;
+    }
+
+    pub fn flush_interval(&self) -> Option<usize> {
+        self.flush_interval
+    }
+
+    pub fn has_flush_interval(&self) -> bool {
+        self.flush_interval.is_some()
+    }
+
+    pub fn set_flush_interval(&mut self, interval: usize) {
+        self.flush_interval = Some(interval);
+    }
+
+    pub fn remove_flush_interval(&mut self) {
+        self.flush_interval = None;
+    }
+
+    pub fn sanitize_headers(headers: &mut HashMap<String, String>) {
+        let framing: Vec<(String, String)> = headers.iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("Transfer-Encoding"))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        let listed: Vec<String> = headers.iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("Connection"))
+            .flat_map(|(_, value)| value.split(',').map(|name| name.trim().to_lowercase()))
+            .collect();
+        remove_hop_by_hop_headers(headers);
+        headers.extend(framing); // chunks are relayed as received
+        headers.retain(|name, _| !listed.contains(&name.to_lowercase())); // unless the sender scoped them to this hop
+    }
+
     pub async fn relay<I, O>(&mut self, input: &mut I, output: &mut O, req: &HashMap<String, String>) -> Result<usize, Error>
         where
-        I: Write + Read + Unpin,
-        O: Write + Read + Unpin,
+        I: Read + Unpin,
+        O: Write + Unpin,
     {
         let length = req.get("Content-Length");
         let encoding = req.get("Transfer-Encoding");
 
-        if encoding.is_some() && encoding.unwrap().contains(&String::from("chunked")) {
+        if encoding.is_some_and(|encoding| is_chunked(encoding)) {
             self.relay_chunked(input, output).await
         } else {
             let length = match length {
-                Some(length) => match length.parse::<usize>() {
+                Some(length) => match usize::try_from(parse_content_length(length)?) {
                     Ok(length) => length,
-                    Err(_) => return Err(Error::InvalidHeader(String::from("Content-Length"))),
+                    Err(_) => return Err(Error::SizeLimitExceeded(usize::MAX)),
                 },
                 None => return Err(Error::InvalidHeader(String::from("Content-Length"))),
             };
@@ -59,20 +111,56 @@ impl Relay {
         }
     }
 
+    pub async fn relay_request<I, O, F>(&mut self, input: &mut I, output: &mut O, mut req: Request, hook: F) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+        O: Write + Unpin,
+        F: FnOnce(&mut Request),
+    {
+        let framing = req.headers().clone();
+        let has_body = req.has_body();
+        hook(&mut req);
+        req.write(output).await?;
+
+        if has_body {
+            self.relay(input, output, &framing).await
+        } else {
+            Ok(0)
+        }
+    }
+
+    pub async fn relay_response<I, O, F>(&mut self, input: &mut I, output: &mut O, method: &str, mut res: Response, hook: F) -> Result<usize, Error>
+        where
+        I: Read + Unpin,
+        O: Write + Unpin,
+        F: FnOnce(&mut Response),
+    {
+        let framing = res.headers().clone();
+        let has_body = body_expected(method, res.status_code());
+        hook(&mut res);
+        res.write(output).await?;
+
+        if has_body {
+            self.relay(input, output, &framing).await
+        } else {
+            Ok(0)
+        }
+    }
+
     pub async fn relay_chunked<I, O>(&mut self, input: &mut I, output: &mut O) -> Result<usize, Error>
         where
-        I: Write + Read + Unpin,
-        O: Write + Read + Unpin,
+        I: Read + Unpin,
+        O: Write + Unpin,
     {
         let limit = match self.length_limit {
             Some(limit) => match limit == 0 {
                 true => return Err(Error::SizeLimitExceeded(limit)),
-                false => Some(limit - self.length),
+                false => Some(limit.saturating_sub(self.length)),
             },
             None => None,
         };
         
-        let length = relay_chunked_stream(input, output, limit).await?;
+        let length = relay_chunked_stream(&mut MinRateStream::new(input, self.min_read_rate), output, limit, self.flush_interval).await?;
         self.length += length;
 
         Ok(length)
@@ -83,15 +171,14 @@ impl Relay {
         I: Read + Unpin,
         O: Write + Unpin,
     {
-        match self.length_limit {
-            Some(limit) => match length + self.length > limit {
-                true => return Err(Error::SizeLimitExceeded(limit)),
-                false => (),
-            },
-            None => (),
-        };
+        if let Some(limit) = self.length_limit {
+            match length.checked_add(self.length) {
+                Some(total) if total <= limit => (),
+                _ => return Err(Error::SizeLimitExceeded(limit)),
+            };
+        }
 
-        let length = relay_sized_stream(input, output, length).await?;
+        let length = relay_sized_stream(&mut MinRateStream::new(input, self.min_read_rate), output, length, self.flush_interval).await?;
         self.length += length;
 
         Ok(length)
@@ -100,5 +187,90 @@ impl Relay {
     pub fn clear(&mut self) {
         self.length = 0;
         self.length_limit = None;
+        self.min_read_rate = None;
+        self.flush_interval = None;
+    }
+}
+
+impl Default for Relay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use async_std::io::Cursor;
+
+    #[async_std::test]
+    async fn relays_request_with_hook() {
+        let mut input = Cursor::new(b"Hello".to_vec());
+        let mut output = Cursor::new(Vec::new());
+        let mut req = Request::new();
+        req.set_method("POST");
+        req.set_header("Content-Length", "5");
+        let mut relay = Relay::new();
+        let length = relay.relay_request(&mut input, &mut output, req, |req| {
+            req.set_header("Via", "1.1 proxy");
+        }).await.unwrap();
+        assert_eq!(length, 5);
+        let output = String::from_utf8(output.into_inner()).unwrap();
+        assert!(output.starts_with("POST / HTTP/1.1\r\n"));
+        assert!(output.contains("Via: 1.1 proxy\r\n"));
+        assert!(output.ends_with("\r\n\r\nHello"));
+    }
+
+    #[async_std::test]
+    async fn relays_bodyless_response() {
+        let mut input = Cursor::new(Vec::new());
+        let mut output = Cursor::new(Vec::new());
+        let mut res = Response::new();
+        res.set_status_code(304);
+        let length = Relay::new().relay_response(&mut input, &mut output, "GET", res, |_| ()).await.unwrap();
+        assert_eq!(length, 0);
+        assert_eq!(output.into_inner(), b"HTTP/1.1 304 OK\r\n\r\n");
+    }
+
+    #[async_std::test]
+    async fn sanitizes_forwarded_headers() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Connection"), String::from("X-Secret"));
+        headers.insert(String::from("X-Secret"), String::from("1"));
+        headers.insert(String::from("Keep-Alive"), String::from("timeout=5"));
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        headers.insert(String::from("Host"), String::from("a.com"));
+        Relay::sanitize_headers(&mut headers);
+        assert_eq!(headers.len(), 2);
+        assert_eq!(headers.get("Transfer-Encoding").unwrap(), "chunked");
+        assert_eq!(headers.get("Host").unwrap(), "a.com");
+
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Connection"), String::from("close, transfer-encoding"));
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        Relay::sanitize_headers(&mut headers);
+        assert!(headers.is_empty());
+    }
+
+    #[async_std::test]
+    async fn consumes_exactly_the_framed_body() {
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("5"));
+        let mut input = "HelloGET / HTTP/1.1\r\n\r\n".as_bytes();
+        let mut output = Vec::new();
+        assert_eq!(Relay::new().relay(&mut input, &mut output, &headers).await.unwrap(), 5);
+        assert_eq!(input, b"GET / HTTP/1.1\r\n\r\n");
+
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Transfer-Encoding"), String::from("chunked"));
+        let mut input = "5\r\nHello\r\n0\r\n\r\nGET / HTTP/1.1\r\n\r\n".as_bytes();
+        let mut output = Vec::new();
+        Relay::new().relay(&mut input, &mut output, &headers).await.unwrap();
+        assert_eq!(input, b"GET / HTTP/1.1\r\n\r\n");
+
+        let mut headers = HashMap::new();
+        headers.insert(String::from("Content-Length"), String::from("5"));
+        let res = Relay::new().relay(&mut "Hel".as_bytes(), &mut Vec::new(), &headers).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
     }
 }
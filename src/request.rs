@@ -1,8 +1,9 @@
 use std::fmt;
+use std::time::SystemTime;
 use std::collections::HashMap;
 use std::collections::hash_map::RandomState;
-use async_std::io::{Read};
-use crate::{Error, read_head, validate_size_constraint, read_headers};
+use async_std::io::{Read, Write};
+use crate::{Error, AuthScheme, combined_log_line, parse_content_length, Body, Response, SmugglingKind, HeadersIter, write_to_stream, flush_stream, Method, remove_hop_by_hop_headers, is_keep_alive, read_head, read_head_with, read_message_headers, ReaderConfig, split_header_list, poll_once, append_header_value, push_header_line, remove_framing_headers};
 
 #[derive(Debug)]
 pub struct Request {
@@ -26,14 +27,29 @@ impl Request {
     pub async fn read<I>(stream: &mut I, limit: Option<usize>) -> Result<Self, Error>
         where
         I: Read + Unpin,
+    {
+        let mut config = ReaderConfig::new();
+        if let Some(limit) = limit {
+            config.set_length_limit(limit);
+        }
+        Self::read_with(stream, &mut config).await
+    }
+
+    pub async fn read_with<I>(stream: &mut I, config: &mut ReaderConfig) -> Result<Self, Error>
+        where
+        I: Read + Unpin,
     {
         let mut req = Self::new();
-        let mut length = 0;
 
         let mut head = Vec::new();
-        length += read_head(stream, &mut head).await?;
-        validate_size_constraint(length, limit)?;
-        req.set_method(match head.get(0) {
+        let length = read_head_with(stream, &mut head, config).await?;
+        config.consume(length)?;
+        if !config.is_lenient() && (head.len() > 3 || head.iter().any(|part| part.is_empty())) {
+            return Err(Error::InvalidData);
+        } else if head.iter().skip(2).any(|part| part.contains(':')) { // header on the request line
+            return Err(Error::InvalidData);
+        }
+        req.set_method(match head.first() {
             Some(method) => method,
             None => return Err(Error::InvalidData),
         });
@@ -46,24 +62,213 @@ impl Request {
             None => "HTTP/0.9",
         });
 
-        if !req.has_version("HTTP/0.9") {
-            read_headers(stream, &mut req.headers, match limit {
-                Some(limit) => Some(limit - length),
-                None => None,
-            }).await?;
+        if let Some(limit) = config.uri_length_limit() {
+            if req.uri.len() > limit {
+                return Err(Error::UriTooLong(limit));
+            }
+        }
+        if req.has_method("CONNECT") && req.connect_authority().is_none() {
+            return Err(Error::InvalidData);
+        } else if !req.version.starts_with("HTTP/") {
+            return Err(Error::UnsupportedVersion(req.version));
+        } else if !req.has_version("HTTP/0.9") {
+            read_message_headers(stream, &mut req.headers, None, config).await?;
         }
 
         Ok(req)
     }
 
+    pub async fn read_with_text_body<I>(stream: &mut I, limit: Option<usize>) -> Result<(Self, String), Error>
+        where
+        I: Read + Unpin,
+    {
+        let mut config = ReaderConfig::new();
+        if let Some(limit) = limit {
+            config.set_length_limit(limit);
+        }
+        let req = Self::read_with(stream, &mut config).await?;
+
+        let mut body = Body::new();
+        if req.has_body() {
+            body.read_with(stream, req.headers(), &mut config).await?;
+        }
+        match String::from_utf8(body.bytes().to_vec()) {
+            Ok(text) => Ok((req, text)),
+            Err(_) => Err(Error::InvalidData),
+        }
+    }
+
+    pub fn parse_message(bytes: &[u8]) -> Result<(Self, Body), Error> {
+        let mut stream = bytes;
+        let mut body = Body::new();
+        let req = poll_once(async { // in-memory reads never pend
+            let req = Self::read(&mut stream, None).await?;
+            if req.has_body() {
+                body.read(&mut stream, req.headers()).await?;
+            }
+            Ok::<_, Error>(req)
+        }).unwrap_or(Err(Error::InvalidData))?;
+        if !stream.is_empty() {
+            return Err(Error::InvalidData);
+        }
+        Ok((req, body))
+    }
+
+    pub async fn read_line<I>(stream: &mut I) -> Result<(String, String, String), Error>
+        where
+        I: Read + Unpin,
+    {
+        let mut head = Vec::new();
+        read_head(stream, &mut head).await?;
+        let mut head = head.into_iter();
+        let method = match head.next() {
+            Some(method) => method,
+            None => return Err(Error::InvalidData),
+        };
+        let uri = match head.next() {
+            Some(uri) => uri,
+            None => return Err(Error::InvalidData),
+        };
+        let version = match head.next() {
+            Some(version) => version,
+            None => String::from("HTTP/0.9"),
+        };
+        Ok((method, uri, version))
+    }
+
+    pub async fn write<O>(&self, stream: &mut O) -> Result<usize, Error>
+        where
+        O: Write + Unpin,
+    {
+        let size = write_to_stream(stream, &self.to_bytes()).await?;
+        flush_stream(stream).await?;
+        Ok(size)
+    }
+
+    pub fn request_line(&self) -> String {
+        format!("{} {} {}", self.method, self.uri, self.version)
+    }
+
+    pub fn access_log_line(&self, status: usize, bytes: usize) -> String {
+        combined_log_line(self, status, bytes, SystemTime::now())
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if self.has_version("HTTP/0.9") {
+            bytes.extend_from_slice(b"GET ");
+            bytes.extend_from_slice(self.uri.as_bytes());
+            bytes.extend_from_slice(b"\r\n");
+        } else {
+            bytes.extend_from_slice(self.request_line().as_bytes());
+            bytes.extend_from_slice(b"\r\n");
+            for (name, value) in self.sorted_headers() {
+                push_header_line(&mut bytes, name, value);
+            }
+            bytes.extend_from_slice(b"\r\n");
+        }
+        bytes
+    }
+
+    pub async fn send_continue<O>(&self, stream: &mut O) -> Result<usize, Error>
+        where
+        O: Write + Unpin,
+    {
+        if !self.expects_continue() {
+            return Ok(0);
+        }
+        let size = write_to_stream(stream, b"HTTP/1.1 100 Continue\r\n\r\n").await?;
+        flush_stream(stream).await?;
+        Ok(size)
+    }
+
+    pub fn into_parts(self) -> (String, String, String, HashMap<String, String>) {
+        (self.method, self.uri, self.version, self.headers)
+    }
+
     pub fn method(&self) -> &String {
         &self.method
     }
 
+    pub fn parsed_method(&self) -> Method {
+        Method::from(self.method.as_str())
+    }
+
     pub fn uri(&self) -> &String {
         &self.uri
     }
 
+    pub fn path(&self) -> &str {
+        let mut path = self.uri.as_str();
+        if let Some(index) = path.find("://") { // absolute-form
+            path = &path[index + 3..];
+            path = path.find('/').map_or("/", |index| &path[index..]);
+        }
+        path.find(['?', '#']).map_or(path, |index| &path[0..index])
+    }
+
+    pub fn query(&self) -> Option<&str> {
+        if self.is_asterisk_form() {
+            return None;
+        }
+        let uri = self.uri.split('#').next().unwrap_or("");
+        uri.find('?').map(|index| &uri[index + 1..])
+    }
+
+    fn uri_components(&self) -> (&str, &str, &str) {
+        let uri = self.uri.as_str();
+        let start = match uri.find("://") { // absolute-form keeps its origin
+            Some(index) => uri[index + 3..].find(['/', '?', '#']).map_or(uri.len(), |offset| index + 3 + offset),
+            None => 0,
+        };
+        let end = uri[start..].find(['?', '#']).map_or(uri.len(), |index| start + index);
+        (&uri[0..start], &uri[start..end], &uri[end..])
+    }
+
+    pub fn connect_authority(&self) -> Option<(String, u16)> {
+        if !self.has_method("CONNECT") {
+            return None;
+        }
+        let index = self.uri.rfind(':')?;
+        let (host, port) = (&self.uri[0..index], &self.uri[index + 1..]);
+        if host.is_empty() || host.contains(['/', '?', '#', '@']) || !port.bytes().all(|b| b.is_ascii_digit()) {
+            return None;
+        }
+        let host = match host.strip_prefix('[') { // IPv6 literal
+            Some(host) => host.strip_suffix(']')?,
+            None if host.contains(':') => return None,
+            None => host,
+        };
+        Some((host.to_string(), port.parse().ok()?))
+    }
+
+    pub fn is_asterisk_form(&self) -> bool {
+        self.uri == "*"
+    }
+
+    pub fn matches_path(&self, pattern: &str) -> Option<HashMap<String, String>> {
+        let mut segments = self.path().split('/').filter(|segment| !segment.is_empty());
+        let mut params = HashMap::new();
+
+        for expected in pattern.split('/').filter(|segment| !segment.is_empty()) {
+            if expected == "*" { // tail
+                params.insert(String::from("*"), segments.collect::<Vec<&str>>().join("/"));
+                return Some(params);
+            }
+            let segment = segments.next()?;
+            if let Some(name) = expected.strip_prefix(':') {
+                params.insert(name.to_string(), segment.to_string());
+            } else if expected != segment {
+                return None;
+            }
+        }
+
+        match segments.next() {
+            Some(_) => None,
+            None => Some(params),
+        }
+    }
+
     pub fn version(&self) -> &String {
         &self.version
     }
@@ -72,18 +277,195 @@ impl Request {
         &self.headers
     }
 
+    pub fn headers_iter(&self) -> HeadersIter<'_> {
+        HeadersIter::new(self.headers.iter())
+    }
+
+    pub fn sorted_headers(&self) -> Vec<(&str, &str)> {
+        let mut headers: Vec<(&str, &str)> = self.headers_iter().collect();
+        headers.sort_unstable(); // stable output regardless of the hasher seed
+        headers
+    }
+
+    pub fn header_values<N: Into<String>>(&self, name: N) -> Vec<String> {
+        match self.header(name) {
+            Some(value) => split_header_list(value),
+            None => Vec::new(),
+        }
+    }
+
     pub fn header<N: Into<String>>(&self, name: N) -> Option<&String> {
         self.headers.get(&name.into())
     }
 
+    pub fn forwarded_for(&self) -> Vec<String> {
+        if let Some(forwarded) = self.header("Forwarded") {
+            forwarded.split(',')
+                .flat_map(|element| element.split(';'))
+                .filter_map(|pair| {
+                    let mut pair = pair.splitn(2, '=');
+                    match (pair.next(), pair.next()) {
+                        (Some(name), Some(value)) if name.trim().eq_ignore_ascii_case("for") => {
+                            Some(value.trim().trim_matches('"').to_string())
+                        },
+                        _ => None,
+                    }
+                })
+                .collect()
+        } else if let Some(forwarded) = self.header("X-Forwarded-For") {
+            forwarded.split(',')
+                .map(|address| address.trim().to_string())
+                .filter(|address| !address.is_empty())
+                .collect()
+        } else {
+            Vec::new()
+        }
+    }
+
+    pub fn authorization(&self) -> Result<Option<AuthScheme>, Error> {
+        match self.header("Authorization") {
+            Some(value) => AuthScheme::parse(value).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    pub fn accepts_encoding(&self, encoding: &str) -> bool {
+        self.encoding_quality(encoding) > 0.0
+    }
+
+    pub fn preferred_encoding(&self, encodings: &[&str]) -> Option<String> {
+        let mut preferred: Option<(&str, f32)> = None;
+        for encoding in encodings {
+            let quality = self.encoding_quality(encoding);
+            let better = match preferred {
+                Some((_, best)) => quality > best,
+                None => true,
+            };
+            if quality > 0.0 && better {
+                preferred = Some((encoding, quality));
+            }
+        }
+        preferred.map(|(encoding, _)| encoding.to_string())
+    }
+
+    fn encoding_quality(&self, encoding: &str) -> f32 {
+        if !self.has_header("Accept-Encoding") {
+            return 1.0;
+        }
+
+        let mut wildcard = None;
+        for item in self.header_values("Accept-Encoding") {
+            let mut params = item.split(';');
+            let coding = params.next().unwrap_or("").trim();
+            let quality = params
+                .filter_map(|param| param.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("q"))
+                .map_or(Some(1.0), |(_, q)| q.trim().parse::<f32>().ok().filter(|q| (0.0..=1.0).contains(q)));
+            let quality = match quality {
+                Some(quality) => quality,
+                None => continue, // malformed q-value
+            };
+            if coding.eq_ignore_ascii_case(encoding) {
+                return quality;
+            } else if coding == "*" {
+                wildcard = Some(quality);
+            }
+        }
+
+        match wildcard {
+            Some(quality) => quality,
+            None if encoding.eq_ignore_ascii_case("identity") => 1.0,
+            None => 0.0,
+        }
+    }
+
+    pub fn te(&self) -> Vec<String> {
+        match self.header("TE") {
+            Some(te) => te.split(',')
+                .filter_map(|coding| coding.split(';').next())
+                .map(|coding| coding.trim().to_lowercase())
+                .filter(|coding| !coding.is_empty())
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn detect_smuggling(&self) -> Option<SmugglingKind> {
+        let mut lengths = Vec::new();
+        let mut encodings = 0;
+        for (name, value) in self.headers.iter() {
+            if name.trim().eq_ignore_ascii_case("Transfer-Encoding") {
+                let last = value.rsplit(',').next().unwrap_or("");
+                let chunked = value.split(',').filter(|coding| coding.trim().eq_ignore_ascii_case("chunked")).count();
+                if name.trim() != name || chunked > 1 || (value.to_lowercase().contains("chunked") && !last.trim().eq_ignore_ascii_case("chunked")) {
+                    return Some(SmugglingKind::ObfuscatedTransferEncoding);
+                }
+                encodings += 1;
+            } else if name.trim().eq_ignore_ascii_case("Content-Length") {
+                for length in value.split(',') {
+                    let length = length.trim();
+                    if !lengths.contains(&length) {
+                        lengths.push(length);
+                    }
+                }
+            }
+        }
+
+        if encodings > 1 {
+            Some(SmugglingKind::ObfuscatedTransferEncoding)
+        } else if lengths.len() > 1 {
+            Some(SmugglingKind::DuplicateContentLength)
+        } else if encodings > 0 && !lengths.is_empty() {
+            Some(SmugglingKind::ContentLengthAndTransferEncoding)
+        } else {
+            None
+        }
+    }
+
+    pub fn semantically_eq(&self, other: &Request) -> bool {
+        let normalize = |headers: &HashMap<String, String>| {
+            let mut headers = headers.clone();
+            remove_hop_by_hop_headers(&mut headers);
+            headers.into_iter()
+                .map(|(name, value)| (name.to_lowercase(), value))
+                .collect::<HashMap<String, String>>()
+        };
+        self.method == other.method
+            && self.uri == other.uri
+            && self.version == other.version
+            && normalize(&self.headers) == normalize(&other.headers)
+    }
+
     pub fn has_method<S: Into<String>>(&self, value: S) -> bool {
         self.method == value.into()
     }
 
+    pub fn method_eq_ignore_case<S: Into<String>>(&self, value: S) -> bool {
+        self.method.eq_ignore_ascii_case(&value.into())
+    }
+
     pub fn has_version<S: Into<String>>(&self, value: S) -> bool {
         self.version == value.into()
     }
 
+    pub fn is_keep_alive(&self) -> bool {
+        is_keep_alive(&self.version, &self.headers)
+    }
+
+    pub fn expects_continue(&self) -> bool {
+        !self.has_version("HTTP/1.0") && self.header("Expect").is_some_and(|expect| expect.eq_ignore_ascii_case("100-continue"))
+    }
+
+    pub fn has_body(&self) -> bool {
+        if self.has_header("Transfer-Encoding") {
+            return true;
+        }
+        match self.header("Content-Length") {
+            Some(length) => length != "0",
+            None => false,
+        }
+    }
+
     pub fn has_headers(&self) -> bool {
         !self.headers.is_empty()
     }
@@ -92,6 +474,20 @@ impl Request {
         self.headers.contains_key(&name.into())
     }
 
+    pub fn reject_unexpected_body(&self) -> Result<(), Error> {
+        match self.parsed_method() {
+            Method::Get | Method::Head | Method::Delete if self.has_body() => Err(Error::InvalidData),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn reject_if_body_exceeds(&self, max: usize) -> Result<(), Error> {
+        match self.header("Content-Length") {
+            Some(length) if parse_content_length(length)? > max as u64 => Err(Error::SizeLimitExceeded(max)),
+            _ => Ok(()), // chunked bodies are only limited while reading
+        }
+    }
+
     pub fn set_method<V: Into<String>>(&mut self, value: V) {
         self.method = value.into();
     }
@@ -100,6 +496,28 @@ impl Request {
         self.uri = value.into();
     }
 
+    pub fn set_path<V: Into<String>>(&mut self, value: V) {
+        let (origin, _, rest) = self.uri_components();
+        self.uri = format!("{}{}{}", origin, value.into(), rest);
+    }
+
+    pub fn rewrite_path<F: FnOnce(&str) -> String>(&mut self, rewrite: F) {
+        let path = rewrite(self.path());
+        self.set_path(path);
+    }
+
+    pub fn set_query<V: Into<String>>(&mut self, value: V) {
+        let (origin, path, rest) = self.uri_components();
+        let fragment = rest.find('#').map_or("", |index| &rest[index..]);
+        self.uri = format!("{}{}?{}{}", origin, path, value.into(), fragment);
+    }
+
+    pub fn remove_query(&mut self) {
+        let (origin, path, rest) = self.uri_components();
+        let fragment = rest.find('#').map_or("", |index| &rest[index..]);
+        self.uri = format!("{}{}{}", origin, path, fragment);
+    }
+
     pub fn set_version<V: Into<String>>(&mut self, value: V) {
         self.version = value.into();
     }
@@ -108,32 +526,64 @@ impl Request {
         self.headers.insert(name.into(), value.into());
     }
 
+    pub fn append_header<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        append_header_value(&mut self.headers, name.into(), value.into());
+    }
+
+    pub fn add_via<P: Into<String>>(&mut self, pseudonym: P) {
+        let protocol = self.version.strip_prefix("HTTP/").unwrap_or(&self.version).to_string();
+        self.append_header("Via", format!("{} {}", protocol, pseudonym.into()));
+    }
+
+    pub fn copy_headers_from(&mut self, other: &Response) {
+        let mut copied = other.headers().clone();
+        remove_framing_headers(&mut copied);
+        for (name, value) in other.headers_iter().filter(|(name, _)| copied.contains_key(*name)) {
+            append_header_value(&mut self.headers, name.to_string(), value.to_string());
+        }
+    }
+
     pub fn remove_header<N: Into<String>>(&mut self, name: N) {
         self.headers.remove(&name.into());
     }
 
+    pub fn remove_hop_by_hop_headers(&mut self) {
+        remove_hop_by_hop_headers(&mut self.headers);
+    }
+
     pub fn clear_headers(&mut self) {
         self.headers.clear();
     }
 
-    pub fn to_string(&self) -> String {
-        let mut output = String::new();
-        if self.has_version("HTTP/0.9") {
-            output.push_str(&format!("GET {}\r\n", self.uri));
-        } else {
-            output.push_str(&format!("{} {} {}\r\n", self.method, self.uri, self.version));
-            for (name, value) in self.headers.iter() {
-                output.push_str(&format!("{}: {}\r\n", name, value));
-            }
-            output.push_str("\r\n");
-        }
-        output
+    pub fn reset(&mut self) {
+        self.method.clear();
+        self.method.push_str("GET");
+        self.uri.clear();
+        self.uri.push('/');
+        self.version.clear();
+        self.version.push_str("HTTP/1.1");
+        self.headers.clear();
+    }
+}
+
+impl Default for Request {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl fmt::Display for Request {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{}", self.to_string())
+        write!(fmt, "{}", String::from_utf8_lossy(&self.to_bytes()))
+    }
+}
+
+impl<'a> IntoIterator for &'a Request {
+    type Item = (&'a str, &'a str);
+    type IntoIter = HeadersIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.headers_iter()
     }
 }
 
@@ -147,6 +597,193 @@ impl From<Request> for String {
 mod tests {
     use super::*;
 
+    async fn round_trip(req: &Request) -> Request {
+        Request::read(&mut req.to_bytes().as_slice(), None).await.unwrap()
+    }
+
+    #[async_std::test]
+    async fn rejects_unterminated_request_line() {
+        let res = Request::read(&mut "GET /\rHost: a.com\r\n\r\n".as_bytes(), None).await;
+        assert_eq!(res.unwrap_err(), Error::ParseError { offset: 5, context: String::from("head") });
+        let mut config = ReaderConfig::new();
+        config.set_lenient(true);
+        let res = Request::read_with(&mut "GET / HTTP/1.1 Host: a.com\r\n\r\n".as_bytes(), &mut config).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+    }
+
+    #[async_std::test]
+    async fn reads_text_body() {
+        let data = "POST / HTTP/1.1\r\nContent-Length: 7\r\n\r\n{\"a\":1}";
+        let (req, text) = Request::read_with_text_body(&mut data.as_bytes(), None).await.unwrap();
+        assert_eq!(req.method(), "POST");
+        assert_eq!(text, "{\"a\":1}");
+        let (_, text) = Request::read_with_text_body(&mut "GET / HTTP/1.1\r\n\r\n".as_bytes(), None).await.unwrap();
+        assert_eq!(text, "");
+        let res = Request::read_with_text_body(&mut data.as_bytes(), Some(40)).await;
+        assert_eq!(res.unwrap_err(), Error::SizeLimitExceeded(40));
+        let mut data = b"POST / HTTP/1.1\r\nContent-Length: 2\r\n\r\n".to_vec();
+        data.extend_from_slice(&[0xC3, 0x28]);
+        let res = Request::read_with_text_body(&mut data.as_slice(), None).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+    }
+
+    #[async_std::test]
+    async fn normalizes_head_when_lenient() {
+        let data = "GET  /path   http/1.1 \r\nH: V\r\n\r\n";
+        assert_eq!(Request::read(&mut data.as_bytes(), None).await.unwrap_err(), Error::InvalidData);
+        let res = Request::read(&mut "GET / http/1.1\r\n\r\n".as_bytes(), None).await;
+        assert_eq!(res.unwrap_err(), Error::UnsupportedVersion(String::from("http/1.1")));
+
+        let mut config = ReaderConfig::new();
+        config.set_lenient(true);
+        let req = Request::read_with(&mut data.as_bytes(), &mut config).await.unwrap();
+        assert_eq!(req.method(), "GET");
+        assert_eq!(req.uri(), "/path");
+        assert_eq!(req.version(), "HTTP/1.1");
+        assert_eq!(req.header("H").unwrap(), "V");
+    }
+
+    #[async_std::test]
+    async fn serializes_to_bytes() {
+        let mut req = Request::new();
+        req.set_uri("/");
+        req.set_header("H", "V");
+        assert_eq!(req.to_bytes(), b"GET / HTTP/1.1\r\nH: V\r\n\r\n");
+        assert_eq!(req.to_bytes(), req.to_string().into_bytes());
+        req.set_version("HTTP/0.9");
+        assert_eq!(req.to_bytes(), b"GET /\r\n");
+    }
+
+    #[async_std::test]
+    async fn appends_via() {
+        let mut req = Request::new();
+        req.add_via("first");
+        req.set_version("HTTP/1.0");
+        req.add_via("second:8080");
+        assert_eq!(req.header("Via").unwrap(), "1.1 first, 1.0 second:8080");
+    }
+
+    #[async_std::test]
+    async fn builds_request_line() {
+        let mut req = Request::new();
+        req.set_uri("/path?a=1");
+        assert_eq!(req.request_line(), "GET /path?a=1 HTTP/1.1");
+    }
+
+    #[async_std::test]
+    async fn parses_authorization() {
+        let mut req = Request::new();
+        assert_eq!(req.authorization(), Ok(None));
+        req.set_header("Authorization", "Bearer token");
+        assert_eq!(req.authorization(), Ok(Some(AuthScheme::Bearer(String::from("token")))));
+        req.set_header("Authorization", "Basic !!!");
+        assert!(req.authorization().is_err());
+    }
+
+    #[async_std::test]
+    async fn resets_for_reuse() {
+        let mut req = Request::read(&mut "POST /a HTTP/1.0\r\nH: V\r\n\r\n".as_bytes(), None).await.unwrap();
+        req.reset();
+        assert_eq!(req.request_line(), "GET / HTTP/1.1");
+        assert!(!req.has_headers());
+    }
+
+    #[async_std::test]
+    async fn matches_path_patterns() {
+        let mut req = Request::new();
+        req.set_uri("/users/42/?tab=posts");
+        assert_eq!(req.path(), "/users/42/");
+        let params = req.matches_path("/users/:id").unwrap();
+        assert_eq!(params.get("id").unwrap(), "42");
+        assert!(req.matches_path("/users").is_none());
+        assert!(req.matches_path("/posts/:id").is_none());
+        assert!(req.matches_path("/users/:id/posts").is_none());
+        req.set_uri("http://a.com/static/css/app.css");
+        assert_eq!(req.path(), "/static/css/app.css");
+        assert_eq!(req.matches_path("/static/*").unwrap().get("*").unwrap(), "css/app.css");
+    }
+
+    #[async_std::test]
+    async fn sends_continue_when_expected() {
+        let mut req = Request::new();
+        let mut output = Vec::new();
+        assert_eq!(req.send_continue(&mut output).await, Ok(0));
+        req.set_header("Expect", "100-Continue");
+        assert!(req.expects_continue());
+        req.send_continue(&mut output).await.unwrap();
+        assert_eq!(output, b"HTTP/1.1 100 Continue\r\n\r\n");
+        req.set_version("HTTP/1.0");
+        assert!(!req.expects_continue());
+    }
+
+    #[async_std::test]
+    async fn splits_header_values() {
+        let mut req = Request::new();
+        assert!(req.header_values("Accept").is_empty());
+        req.set_header("Accept", "text/html, application/json;q=0.9");
+        assert_eq!(req.header_values("Accept"), vec!["text/html", "application/json;q=0.9"]);
+    }
+
+    #[async_std::test]
+    async fn negotiates_accept_encoding() {
+        let mut req = Request::new();
+        assert!(req.accepts_encoding("gzip"));
+        assert_eq!(req.preferred_encoding(&["br", "gzip"]), Some(String::from("br")));
+        req.set_header("Accept-Encoding", "gzip;q=0.5, br, identity;q=0");
+        assert!(req.accepts_encoding("GZIP"));
+        assert!(!req.accepts_encoding("deflate"));
+        assert!(!req.accepts_encoding("identity"));
+        assert_eq!(req.preferred_encoding(&["gzip", "br"]), Some(String::from("br")));
+        assert_eq!(req.preferred_encoding(&["deflate", "identity"]), None);
+        req.set_header("Accept-Encoding", "*;q=0.1, gzip;q=0");
+        assert!(!req.accepts_encoding("gzip"));
+        assert!(req.accepts_encoding("identity"));
+        req.set_header("Accept-Encoding", "");
+        assert_eq!(req.preferred_encoding(&["gzip", "identity"]), Some(String::from("identity")));
+        req.set_header("Accept-Encoding", "gzip; Q=0.2, br;q=0.1");
+        assert_eq!(req.preferred_encoding(&["gzip", "br"]), Some(String::from("gzip")));
+        req.set_header("Accept-Encoding", "gzip;q=5, br;q=-1, deflate;q=0.3");
+        assert!(!req.accepts_encoding("gzip"));
+        assert!(!req.accepts_encoding("br"));
+        assert_eq!(req.preferred_encoding(&["gzip", "br", "deflate"]), Some(String::from("deflate")));
+    }
+
+    #[async_std::test]
+    async fn recognizes_asterisk_form() {
+        let req = Request::read(&mut "OPTIONS * HTTP/1.1\r\n\r\n".as_bytes(), None).await.unwrap();
+        assert!(req.is_asterisk_form());
+        assert_eq!(req.path(), "*");
+        assert_eq!(req.query(), None);
+        let mut req = Request::new();
+        req.set_uri("/a?b=1#c");
+        assert!(!req.is_asterisk_form());
+        assert_eq!(req.query(), Some("b=1"));
+    }
+
+    #[async_std::test]
+    async fn parses_connect_authority() {
+        let req = Request::read(&mut "CONNECT example.com:443 HTTP/1.1\r\n\r\n".as_bytes(), None).await.unwrap();
+        assert_eq!(req.connect_authority(), Some((String::from("example.com"), 443)));
+        let mut req = Request::new();
+        req.set_method("CONNECT");
+        req.set_uri("[::1]:8080");
+        assert_eq!(req.connect_authority(), Some((String::from("::1"), 8080)));
+        req.set_uri("example.com");
+        assert_eq!(req.connect_authority(), None);
+        req.set_uri("example.com:99999");
+        assert_eq!(req.connect_authority(), None);
+        let res = Request::read(&mut "CONNECT /path HTTP/1.1\r\n\r\n".as_bytes(), None).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+    }
+
+    #[async_std::test]
+    async fn parses_te_header() {
+        let mut req = Request::new();
+        assert!(req.te().is_empty());
+        req.set_header("TE", "trailers, Deflate;q=0.5 ,");
+        assert_eq!(req.te(), vec!["trailers", "deflate"]);
+    }
+
     #[async_std::test]
     async fn creates_from_stream() {
         let stream = String::from("GET / HTTP/1.1\r\nH: V\r\n\r\n");
@@ -157,4 +794,217 @@ mod tests {
         assert_eq!(req.headers().len(), 1);
         assert_eq!(req.header("H").unwrap(), "V");
     }
+
+    #[async_std::test]
+    async fn reads_request_line() {
+        let mut stream = "DELETE /path HTTP/1.1\r\nH: V\r\n\r\n".as_bytes();
+        let (method, uri, version) = Request::read_line(&mut stream).await.unwrap();
+        assert_eq!((method.as_str(), uri.as_str(), version.as_str()), ("DELETE", "/path", "HTTP/1.1"));
+        assert_eq!(stream, b"H: V\r\n\r\n");
+    }
+
+    #[async_std::test]
+    async fn limits_head_exactly() {
+        let stream = "GET / HTTP/1.1\r\nH: V\r\n\r\n";
+        let res = Request::read(&mut stream.as_bytes(), Some(16)).await;
+        assert_eq!(res.unwrap_err(), Error::SizeLimitExceeded(16));
+        assert!(Request::read(&mut stream.as_bytes(), Some(24)).await.is_ok());
+        assert!(Request::read(&mut "GET /\r\n".as_bytes(), Some(7)).await.is_ok());
+    }
+
+    #[async_std::test]
+    async fn removes_hop_by_hop_headers() {
+        let mut req = Request::new();
+        req.set_header("Host", "example.com");
+        req.set_header("Connection", "keep-alive, X-Custom");
+        req.set_header("Keep-Alive", "timeout=5");
+        req.set_header("Transfer-Encoding", "chunked");
+        req.set_header("x-custom", "1");
+        req.remove_hop_by_hop_headers();
+        assert_eq!(req.headers().len(), 1);
+        assert_eq!(req.header("Host").unwrap(), "example.com");
+    }
+
+    #[async_std::test]
+    async fn reads_forwarded_for() {
+        let mut req = Request::new();
+        assert!(req.forwarded_for().is_empty());
+        req.set_header("X-Forwarded-For", "203.0.113.1 ,198.51.100.2,  ");
+        assert_eq!(req.forwarded_for(), vec!["203.0.113.1", "198.51.100.2"]);
+        req.set_header("Forwarded", "for=192.0.2.43;proto=http, For=\"[2001:db8:cafe::17]:4711\"");
+        assert_eq!(req.forwarded_for(), vec!["192.0.2.43", "[2001:db8:cafe::17]:4711"]);
+    }
+
+    #[async_std::test]
+    async fn parses_method() {
+        let mut req = Request::new();
+        assert_eq!(req.parsed_method(), Method::Get);
+        req.set_method("get");
+        assert_eq!(req.parsed_method(), Method::Other(String::from("get")));
+        assert!(req.method_eq_ignore_case("GET"));
+        req.set_method("PURGE");
+        assert_eq!(req.parsed_method().to_string(), "PURGE");
+    }
+
+    #[async_std::test]
+    async fn converts_into_parts() {
+        let mut req = Request::new();
+        req.set_header("H", "V");
+        let (method, uri, version, headers) = req.into_parts();
+        assert_eq!(method, "GET");
+        assert_eq!(uri, "/");
+        assert_eq!(version, "HTTP/1.1");
+        assert_eq!(headers.get("H").unwrap(), "V");
+    }
+
+    #[async_std::test]
+    async fn rejects_declared_body_over_limit() {
+        let mut req = Request::new();
+        assert_eq!(req.reject_if_body_exceeds(10), Ok(()));
+        req.set_header("Content-Length", "10");
+        assert_eq!(req.reject_if_body_exceeds(10), Ok(()));
+        req.set_header("Content-Length", "11");
+        assert_eq!(req.reject_if_body_exceeds(10), Err(Error::SizeLimitExceeded(10)));
+        req.set_header("Content-Length", "1x");
+        assert_eq!(req.reject_if_body_exceeds(10), Err(Error::InvalidHeader(String::from("Content-Length"))));
+    }
+
+    #[async_std::test]
+    async fn rejects_unexpected_body() {
+        let mut req = Request::new();
+        assert_eq!(req.reject_unexpected_body(), Ok(()));
+        req.set_header("Content-Length", "0");
+        assert_eq!(req.reject_unexpected_body(), Ok(()));
+        req.set_header("Content-Length", "5");
+        assert_eq!(req.reject_unexpected_body(), Err(Error::InvalidData));
+        req.set_method("POST");
+        assert_eq!(req.reject_unexpected_body(), Ok(()));
+        req.set_method("HEAD");
+        req.remove_header("Content-Length");
+        req.set_header("Transfer-Encoding", "chunked");
+        assert_eq!(req.reject_unexpected_body(), Err(Error::InvalidData));
+    }
+
+    #[async_std::test]
+    async fn iterates_headers() {
+        let mut req = Request::new();
+        req.set_header("H", "V");
+        let mut headers = Vec::new();
+        for (name, value) in &req {
+            headers.push((name, value));
+        }
+        assert_eq!(headers, vec![("H", "V")]);
+    }
+
+    #[async_std::test]
+    async fn detects_smuggling() {
+        let mut req = Request::new();
+        req.set_header("Content-Length", "5");
+        assert_eq!(req.detect_smuggling(), None);
+        req.set_header("Transfer-Encoding", "chunked");
+        assert_eq!(req.detect_smuggling(), Some(SmugglingKind::ContentLengthAndTransferEncoding));
+        req.set_header("content-length", "6");
+        assert_eq!(req.detect_smuggling(), Some(SmugglingKind::DuplicateContentLength));
+        req.clear_headers();
+        req.set_header("Transfer-Encoding ", "chunked");
+        assert_eq!(req.detect_smuggling(), Some(SmugglingKind::ObfuscatedTransferEncoding));
+        req.clear_headers();
+        req.set_header("Transfer-Encoding", "xchunked");
+        assert_eq!(req.detect_smuggling(), Some(SmugglingKind::ObfuscatedTransferEncoding));
+        req.set_header("Transfer-Encoding", "CHUNKED");
+        assert_eq!(req.detect_smuggling(), None);
+        req.set_header("Transfer-Encoding", "gzip, Chunked");
+        assert_eq!(req.detect_smuggling(), None);
+    }
+
+    #[async_std::test]
+    async fn detects_smuggling_from_repeated_lines() {
+        let mut stream = "POST / HTTP/1.1\r\nContent-Length: 5\r\nContent-Length: 6\r\n\r\n".as_bytes();
+        let req = Request::read(&mut stream, None).await.unwrap();
+        assert_eq!(req.header("Content-Length").unwrap(), "5, 6");
+        assert_eq!(req.detect_smuggling(), Some(SmugglingKind::DuplicateContentLength));
+        let mut stream = "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTransfer-Encoding: chunked\r\n\r\n".as_bytes();
+        let req = Request::read(&mut stream, None).await.unwrap();
+        assert_eq!(req.detect_smuggling(), Some(SmugglingKind::ObfuscatedTransferEncoding));
+        let mut stream = "POST / HTTP/1.1\r\nAccept: a\r\nAccept: b\r\n\r\n".as_bytes();
+        let req = Request::read(&mut stream, None).await.unwrap();
+        assert_eq!(req.header("Accept").unwrap(), "a, b");
+        assert_eq!(req.detect_smuggling(), None);
+    }
+
+    #[async_std::test]
+    async fn compares_semantically() {
+        let mut req = Request::new();
+        req.set_header("Host", "example.com");
+        req.set_header("Accept", "*/*");
+        let mut other = Request::new();
+        other.set_header("accept", "*/*");
+        other.set_header("host", "example.com");
+        other.set_header("Connection", "close");
+        assert!(req.semantically_eq(&other));
+        other.set_uri("/other");
+        assert!(!req.semantically_eq(&other));
+    }
+
+    #[async_std::test]
+    async fn round_trips_through_bytes() {
+        for (method, uri, headers) in [
+            ("GET", "/", vec![]),
+            ("POST", "/a/b?x=1&y=2", vec![("Host", "a.com"), ("Content-Length", "5")]),
+            ("DELETE", "*", vec![("X-A", "1"), ("X-B", "two words"), ("X-C", "a, b")]),
+        ] {
+            let mut req = Request::new();
+            req.set_method(method);
+            req.set_uri(uri);
+            for (name, value) in headers {
+                req.set_header(name, value);
+            }
+            let other = round_trip(&req).await;
+            assert_eq!(other.method(), req.method());
+            assert_eq!(other.uri(), req.uri());
+            assert_eq!(other.version(), req.version());
+            assert_eq!(other.headers(), req.headers()); // HashMap equality ignores order
+        }
+    }
+
+    #[test]
+    fn parses_complete_message() {
+        let (req, body) = Request::parse_message(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nHello").unwrap();
+        assert!(req.has_method("POST"));
+        assert_eq!(body.bytes(), b"Hello");
+        let (_, body) = Request::parse_message(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n0\r\n\r\n").unwrap();
+        assert_eq!(body.bytes(), b"Hello");
+        let (_, body) = Request::parse_message(b"GET / HTTP/1.1\r\n\r\n").unwrap();
+        assert!(body.bytes().is_empty());
+        assert_eq!(Request::parse_message(b"GET / HTTP/1.1\r\n\r\nextra").unwrap_err(), Error::InvalidData);
+        assert!(Request::parse_message(b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nHel").is_err());
+    }
+
+    #[async_std::test]
+    async fn serializes_headers_in_stable_order() {
+        let mut req = Request::new();
+        for name in ["X-C", "X-A", "X-B", "Host"] {
+            req.set_header(name, "1");
+        }
+        assert_eq!(req.sorted_headers(), vec![("Host", "1"), ("X-A", "1"), ("X-B", "1"), ("X-C", "1")]);
+        assert_eq!(req.to_bytes(), b"GET / HTTP/1.1\r\nHost: 1\r\nX-A: 1\r\nX-B: 1\r\nX-C: 1\r\n\r\n");
+    }
+
+    #[async_std::test]
+    async fn rewrites_path_and_query() {
+        let mut req = Request::new();
+        req.set_uri("/api/users?id=1#top");
+        req.rewrite_path(|path| path.trim_start_matches("/api").to_string());
+        assert_eq!(req.uri(), "/users?id=1#top");
+        req.set_query("id=2");
+        assert_eq!(req.uri(), "/users?id=2#top");
+        req.remove_query();
+        assert_eq!(req.uri(), "/users#top");
+        req.set_uri("http://a.com?x=1");
+        req.set_path("/b");
+        assert_eq!(req.uri(), "http://a.com/b?x=1");
+        req.set_uri("http://a.com/c");
+        req.set_query("y=2");
+        assert_eq!(req.uri(), "http://a.com/c?y=2");
+    }
 }
@@ -1,8 +1,10 @@
 use std::fmt;
+use std::convert::TryFrom;
 use std::collections::HashMap;
 use std::collections::hash_map::RandomState;
-use async_std::io::{Read};
-use crate::{Error, read_head, read_headers, validate_size_constraint};
+use async_std::prelude::*;
+use async_std::io::{Read, Write};
+use crate::{Error, Body, Request, Cookie, ContentRange, HeadersIter, write_to_stream, flush_stream, remove_hop_by_hop_headers, is_chunked, is_keep_alive, read_head_with, read_message_headers, read_error, reason_phrase, parse_content_length, ReaderConfig, RetryAfter, split_header_list, append_header_line, push_header_line, remove_framing_headers};
 
 #[derive(Debug)]
 pub struct Response {
@@ -10,6 +12,7 @@ pub struct Response {
     status_message: String,
     version: String,
     headers: HashMap<String, String>,
+    repeated: Vec<(String, String)>,
 }
 
 impl Response {
@@ -20,20 +23,101 @@ impl Response {
             status_message: String::from("OK"),
             version: String::from("HTTP/1.1"),
             headers: HashMap::with_hasher(RandomState::new()),
+            repeated: Vec::new(),
         }
     }
 
+    pub fn method_not_allowed(methods: &[&str]) -> Self {
+        let mut res = Self::new();
+        res.set_status_code_with_default_message(405);
+        res.set_allow(methods);
+        res
+    }
+
+    pub fn redirect<L: Into<String>>(status: usize, location: L) -> Result<Self, Error> {
+        if !(300..400).contains(&status) {
+            return Err(Error::InvalidData);
+        }
+        let mut res = Self::new();
+        res.set_status_code_with_default_message(status);
+        res.set_header("Location", location);
+        Ok(res)
+    }
+
+    pub fn found<L: Into<String>>(location: L) -> Self {
+        let mut res = Self::new();
+        res.set_status_code_with_default_message(302);
+        res.set_header("Location", location);
+        res
+    }
+
+    pub fn moved_permanently<L: Into<String>>(location: L) -> Self {
+        let mut res = Self::new();
+        res.set_status_code_with_default_message(301);
+        res.set_header("Location", location);
+        res
+    }
+
     pub async fn read<I>(stream: &mut I, limit: Option<usize>) -> Result<Self, Error>
         where
         I: Read + Unpin,
+    {
+        let mut config = ReaderConfig::new();
+        if let Some(limit) = limit {
+            config.set_length_limit(limit);
+        }
+        Self::read_with(stream, &mut config).await
+    }
+
+    pub async fn read_with<I>(stream: &mut I, config: &mut ReaderConfig) -> Result<Self, Error>
+        where
+        I: Read + Unpin,
+    {
+        Self::read_with_consumed(stream, config, &mut Vec::new()).await
+    }
+
+    pub async fn read_with_consumed<I>(stream: &mut I, config: &mut ReaderConfig, consumed: &mut Vec<u8>) -> Result<Self, Error>
+        where
+        I: Read + Unpin,
     {
         let mut req = Self::new();
-        let mut length = 0;
+
+        consumed.clear(); // on HTTP/0.9 these bytes start the body
+        let mut start = 0;
+        let mut skipped = 0;
+        while consumed.len() - start < 5 {
+            let mut byte = [0u8];
+            match stream.read(&mut byte).await {
+                Ok(0) => return Err(Error::StreamNotReadable),
+                Ok(_) => consumed.push(byte[0]),
+                Err(err) => return Err(read_error(err)),
+            };
+            let line = &consumed[start..];
+            if line == b"\r\n" || (line == b"\n" && config.is_lenient()) { // stray empty line before the status line
+                match config.empty_lines_limit() {
+                    Some(limit) if skipped < limit => {
+                        skipped += 1;
+                        start = consumed.len();
+                        continue;
+                    },
+                    _ => return Err(Error::InvalidData), // malformed rather than a simple response, as in Request
+                };
+            } else if line == b"\r" {
+                continue;
+            }
+            let matches = match config.is_lenient() {
+                true => line.eq_ignore_ascii_case(&b"HTTP/"[0..line.len()]),
+                false => line == &b"HTTP/"[0..line.len()],
+            };
+            if !matches { // simple response without status line
+                return Err(Error::UnsupportedVersion(String::from("HTTP/0.9")));
+            }
+        }
 
         let mut head = Vec::new();
-        length += read_head(stream, &mut head).await?;
-        validate_size_constraint(length, limit)?;
-        req.set_version(match head.get(0) {
+        let length = read_head_with(&mut consumed[start..].chain(&mut *stream), &mut head, config).await?;
+        config.consume(start + length)?;
+        req.set_version(match head.first() {
             Some(version) => version,
             None => return Err(Error::InvalidData),
         });
@@ -44,19 +128,80 @@ impl Response {
             },
             None => return Err(Error::InvalidData),
         });
-        req.set_status_message(match head.get(2) {
-            Some(message) => message,
-            None => return Err(Error::InvalidData),
-        });
+        req.set_status_message(head.get(2..).map_or(String::new(), |words| words.join(" "))); // reason phrase is optional
 
-        read_headers(stream, &mut req.headers, match limit {
-            Some(limit) => Some(limit - length),
-            None => None,
-        }).await?;
+        read_message_headers(stream, &mut req.headers, Some(&mut req.repeated), config).await?;
 
         Ok(req)
     }
 
+    pub async fn read_interim<I>(stream: &mut I, limit: Option<usize>) -> Result<(Self, bool), Error>
+        where
+        I: Read + Unpin,
+    {
+        let res = Self::read(stream, limit).await?;
+        let interim = res.is_interim();
+        Ok((res, interim))
+    }
+
+    pub async fn write<O>(&self, stream: &mut O) -> Result<usize, Error>
+        where
+        O: Write + Unpin,
+    {
+        let size = write_to_stream(stream, &self.to_bytes()).await?;
+        flush_stream(stream).await?;
+        Ok(size)
+    }
+
+    pub fn status_line(&self) -> String {
+        format!("{} {} {}", self.version, self.status_code, self.status_message)
+    }
+
+    pub async fn write_with_body<O>(&mut self, stream: &mut O, body: &mut Body) -> Result<usize, Error>
+        where
+        O: Write + Unpin,
+    {
+        if self.is_informational() || self.status_code == 204 || self.status_code == 304 {
+            if body.length() > 0 {
+                return Err(Error::InvalidData);
+            }
+        } else if self.header("Transfer-Encoding").is_some_and(|encoding| is_chunked(encoding)) {
+            return Err(Error::InvalidData); // the body is written as is, without chunk framing
+        } else {
+            match self.header("Content-Length") {
+                Some(length) if parse_content_length(length)? != body.length() as u64 => return Err(Error::InvalidData),
+                Some(_) => (),
+                None if self.has_header("Transfer-Encoding") => (),
+                None => self.set_content_length(body.length()),
+            };
+        }
+
+        let size = self.write(stream).await?;
+        match body.write(stream).await {
+            Ok(length) => Ok(size + length),
+            Err(Error::StreamNotWritable) => Err(Error::PartialWrite(size)),
+            Err(Error::PartialWrite(length)) => Err(Error::PartialWrite(size + length)),
+            Err(err) => Err(err),
+        }
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        if !self.has_version("HTTP/0.9") {
+            bytes.extend_from_slice(self.status_line().as_bytes());
+            bytes.extend_from_slice(b"\r\n");
+            for (name, value) in self.sorted_headers() {
+                push_header_line(&mut bytes, name, value);
+            }
+            bytes.extend_from_slice(b"\r\n");
+        }
+        bytes
+    }
+
+    pub fn into_parts(self) -> (usize, String, String, HashMap<String, String>) {
+        (self.status_code, self.status_message, self.version, self.headers)
+    }
+
     pub fn status_code(&self) -> usize {
         self.status_code
     }
@@ -73,14 +218,93 @@ impl Response {
         &self.headers
     }
 
+    pub fn headers_iter(&self) -> HeadersIter<'_> {
+        HeadersIter::with_repeated(self.headers.iter(), &self.repeated)
+    }
+
+    pub fn sorted_headers(&self) -> Vec<(&str, &str)> {
+        let mut headers: Vec<(&str, &str)> = self.headers_iter().collect();
+        headers.sort_by(|a, b| a.0.cmp(b.0)); // stable output regardless of the hasher seed, repeated lines keep their order
+        headers
+    }
+
+    pub fn header_values<N: Into<String>>(&self, name: N) -> Vec<String> {
+        let name = name.into();
+        match self.headers.get(&name) {
+            Some(value) if name.eq_ignore_ascii_case("Set-Cookie") => std::iter::once(value)
+                .chain(self.repeated.iter().filter(|(repeated, _)| *repeated == name).map(|(_, value)| value))
+                .cloned()
+                .collect(),
+            Some(value) => split_header_list(value),
+            None => Vec::new(),
+        }
+    }
+
     pub fn header<N: Into<String>>(&self, name: N) -> Option<&String> {
         self.headers.get(&name.into())
     }
 
+    pub fn location(&self) -> Option<String> {
+        self.header("Location").cloned()
+    }
+
+    pub fn content_range(&self) -> Option<ContentRange> {
+        self.headers.get("Content-Range").and_then(|value| ContentRange::parse(value))
+    }
+
+    pub fn retry_after_value(&self) -> Option<RetryAfter> {
+        self.headers.get("Retry-After").and_then(|value| RetryAfter::parse(value))
+    }
+
+    pub fn expected_body_length(&self) -> Option<usize> {
+        if self.header("Transfer-Encoding").is_some_and(|encoding| is_chunked(encoding)) {
+            return None;
+        }
+        match self.header("Content-Length") {
+            Some(length) => parse_content_length(length).ok().and_then(|length| usize::try_from(length).ok()),
+            None => None,
+        }
+    }
+
+    pub fn cookies(&self) -> Vec<&str> {
+        self.headers_iter()
+            .filter(|(name, _)| name.eq_ignore_ascii_case("Set-Cookie"))
+            .map(|(_, value)| value)
+            .collect()
+    }
+
     pub fn has_status_code(&self, value: usize) -> bool {
         self.status_code == value
     }
 
+    pub fn is_informational(&self) -> bool {
+        (100..200).contains(&self.status_code)
+    }
+
+    pub fn is_interim(&self) -> bool {
+        self.is_informational() && self.status_code != 101
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    pub fn is_redirection(&self) -> bool {
+        (300..400).contains(&self.status_code)
+    }
+
+    pub fn is_client_error(&self) -> bool {
+        (400..500).contains(&self.status_code)
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.status_code)
+    }
+
+    pub fn is_keep_alive(&self) -> bool {
+        is_keep_alive(&self.version, &self.headers)
+    }
+
     pub fn has_version<V: Into<String>>(&self, value: V) -> bool {
         self.version == value.into()
     }
@@ -97,6 +321,11 @@ impl Response {
         self.status_code = value;
     }
 
+    pub fn set_status_code_with_default_message(&mut self, value: usize) {
+        self.status_code = value;
+        self.status_message = String::from(reason_phrase(value).unwrap_or(""));
+    }
+
     pub fn set_status_message<V: Into<String>>(&mut self, value: V) {
         self.status_message = value.into();
     }
@@ -105,34 +334,136 @@ impl Response {
         self.version = value.into();
     }
 
+    pub fn set_keep_alive(&mut self, value: bool) {
+        let mut tokens = Vec::new();
+        self.headers.retain(|name, list| {
+            if !name.eq_ignore_ascii_case("Connection") {
+                return true;
+            }
+            tokens.extend(split_header_list(list).into_iter().filter(|token| {
+                !token.eq_ignore_ascii_case("close") && !token.eq_ignore_ascii_case("keep-alive") // other options such as Upgrade stay
+            }));
+            false
+        });
+        if self.has_version("HTTP/1.0") && value {
+            tokens.push(String::from("keep-alive"));
+        } else if !self.has_version("HTTP/1.0") && !value {
+            tokens.push(String::from("close"));
+        }
+        if !tokens.is_empty() {
+            self.set_header("Connection", tokens.join(", "));
+        }
+    }
+
     pub fn set_header<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
-        self.headers.insert(name.into(), value.into());
+        let name = name.into();
+        self.repeated.retain(|(repeated, _)| *repeated != name);
+        self.headers.insert(name, value.into());
+    }
+
+    pub fn append_header<N: Into<String>, V: Into<String>>(&mut self, name: N, value: V) {
+        append_header_line(&mut self.headers, &mut self.repeated, name.into(), value.into());
+    }
+
+    pub fn add_via<P: Into<String>>(&mut self, pseudonym: P) {
+        let protocol = self.version.strip_prefix("HTTP/").unwrap_or(&self.version).to_string();
+        self.append_header("Via", format!("{} {}", protocol, pseudonym.into()));
+    }
+
+    pub fn copy_headers_from(&mut self, other: &Request) {
+        let mut copied = other.headers().clone();
+        remove_framing_headers(&mut copied);
+        for (name, value) in other.headers_iter().filter(|(name, _)| copied.contains_key(*name)) {
+            append_header_line(&mut self.headers, &mut self.repeated, name.to_string(), value.to_string());
+        }
     }
 
     pub fn remove_header<N: Into<String>>(&mut self, name: N) {
-        self.headers.remove(&name.into());
+        let name = name.into();
+        self.repeated.retain(|(repeated, _)| *repeated != name);
+        self.headers.remove(&name);
+    }
+
+    pub fn set_cookie(&mut self, cookie: &Cookie) -> Result<(), Error> {
+        if !cookie.is_valid() {
+            return Err(Error::InvalidData);
+        }
+        append_header_line(&mut self.headers, &mut self.repeated, String::from("Set-Cookie"), cookie.to_string());
+        Ok(())
+    }
+
+    pub fn www_authenticate<S: AsRef<str>, R: AsRef<str>>(&mut self, scheme: S, realm: R) {
+        self.set_header("WWW-Authenticate", format!("{} realm=\"{}\"", scheme.as_ref(), realm.as_ref().replace('\\', "\\\\").replace('"', "\\\"")));
+    }
+
+    pub fn retry_after<R: Into<RetryAfter>>(&mut self, value: R) {
+        self.set_header("Retry-After", value.into().to_header_value());
+    }
+
+    pub fn set_chunked(&mut self) {
+        self.headers.retain(|name, _| !name.eq_ignore_ascii_case("Content-Length") && !name.eq_ignore_ascii_case("Transfer-Encoding"));
+        self.set_header("Transfer-Encoding", "chunked");
+    }
+
+    pub fn set_content_length(&mut self, length: usize) {
+        self.headers.retain(|name, _| !name.eq_ignore_ascii_case("Transfer-Encoding") && !name.eq_ignore_ascii_case("Content-Length"));
+        self.set_header("Content-Length", length.to_string());
+    }
+
+    pub fn set_allow(&mut self, methods: &[&str]) {
+        self.set_header("Allow", methods.join(", "));
+    }
+
+    pub fn set_trailer<N: AsRef<str>>(&mut self, names: &[N]) {
+        let names: Vec<&str> = names.iter().map(|name| name.as_ref()).collect();
+        self.set_header("Trailer", names.join(", "));
+    }
+
+    pub fn clear_cookies(&mut self) {
+        self.headers.retain(|name, _| !name.eq_ignore_ascii_case("Set-Cookie"));
+        self.repeated.retain(|(name, _)| !name.eq_ignore_ascii_case("Set-Cookie"));
+    }
+
+    pub fn remove_hop_by_hop_headers(&mut self) {
+        remove_hop_by_hop_headers(&mut self.headers);
+        let headers = &self.headers;
+        self.repeated.retain(|(name, _)| headers.contains_key(name));
     }
 
     pub fn clear_headers(&mut self) {
         self.headers.clear();
+        self.repeated.clear();
     }
 
-    pub fn to_string(&self) -> String {
-        let mut output = String::new();
-        if !self.has_version("HTTP/0.9") {
-            output.push_str(&format!("{} {} {}\r\n", self.version, self.status_code, self.status_message));
-            for (name, value) in self.headers.iter() {
-                output.push_str(&format!("{}: {}\r\n", name, value));
-            }
-            output.push_str("\r\n");
-        }
-        output
+    pub fn reset(&mut self) {
+        self.status_code = 200;
+        self.status_message.clear();
+        self.status_message.push_str("OK");
+        self.version.clear();
+        self.version.push_str("HTTP/1.1");
+        self.headers.clear();
+        self.repeated.clear();
+    }
+}
+
+impl Default for Response {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl fmt::Display for Response {
     fn fmt(&self, fmt: &mut fmt::Formatter) -> fmt::Result {
-        write!(fmt, "{}", self.to_string())
+        write!(fmt, "{}", String::from_utf8_lossy(&self.to_bytes()))
+    }
+}
+
+impl<'a> IntoIterator for &'a Response {
+    type Item = (&'a str, &'a str);
+    type IntoIter = HeadersIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.headers_iter()
     }
 }
 
@@ -145,6 +476,12 @@ impl From<Response> for String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::SameSite;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    async fn round_trip(res: &Response) -> Response {
+        Response::read(&mut res.to_bytes().as_slice(), None).await.unwrap()
+    }
     
     #[async_std::test]
     async fn creates_from_stream() {
@@ -156,4 +493,374 @@ mod tests {
         assert_eq!(res.headers().len(), 1);
         assert_eq!(res.header("H").unwrap(), "V");
     }
+
+    #[async_std::test]
+    async fn rejects_simple_response() {
+        let res = Response::read(&mut "<html></html>".as_bytes(), None).await;
+        assert_eq!(res.unwrap_err(), Error::UnsupportedVersion(String::from("HTTP/0.9")));
+
+        let mut stream = "HTTX<html></html>".as_bytes();
+        let mut consumed = Vec::new();
+        let res = Response::read_with_consumed(&mut stream, &mut ReaderConfig::new(), &mut consumed).await;
+        assert_eq!(res.unwrap_err(), Error::UnsupportedVersion(String::from("HTTP/0.9")));
+        let mut body = Body::new();
+        body.prime(&consumed);
+        body.read_sized(&mut stream, 17).await.unwrap();
+        assert_eq!(body.bytes(), b"HTTX<html></html>");
+    }
+
+    #[async_std::test]
+    async fn probes_status_line_after_empty_lines() {
+        let mut config = ReaderConfig::new();
+        config.set_empty_lines_limit(2);
+        let res = Response::read_with(&mut "\r\nHTTP/1.1 200 OK\r\n\r\n".as_bytes(), &mut config).await.unwrap();
+        assert_eq!(res.status_code(), 200);
+        let res = Response::read_with(&mut "\r\n\r\n\r\nHTTP/1.1 200 OK\r\n\r\n".as_bytes(), &mut ReaderConfig::new()).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+        let res = Response::read_with(&mut "\r\nOK\r\n".as_bytes(), &mut ReaderConfig::new()).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+        let req = Request::read_with(&mut "\r\nGET /\r\n".as_bytes(), &mut ReaderConfig::new()).await;
+        assert_eq!(req.unwrap_err(), Error::InvalidData);
+        let mut config = ReaderConfig::new();
+        config.set_empty_lines_limit(2);
+        let res = Response::read_with(&mut "\r\n\r\n\r\nHTTP/1.1 200 OK\r\n\r\n".as_bytes(), &mut config).await;
+        assert_eq!(res.unwrap_err(), Error::InvalidData);
+
+        let mut config = ReaderConfig::new();
+        config.set_lenient(true);
+        let res = Response::read_with(&mut "http/1.1 200 OK\r\n\r\n".as_bytes(), &mut config).await.unwrap();
+        assert_eq!(res.version(), "HTTP/1.1");
+        let res = Response::read_with(&mut "http/1.1 200 OK\r\n\r\n".as_bytes(), &mut ReaderConfig::new()).await;
+        assert_eq!(res.unwrap_err(), Error::UnsupportedVersion(String::from("HTTP/0.9")));
+    }
+
+    #[async_std::test]
+    async fn limits_head_exactly() {
+        let stream = "HTTP/1.1 200 OK\r\n\r\n";
+        let res = Response::read(&mut stream.as_bytes(), Some(17)).await;
+        assert_eq!(res.unwrap_err(), Error::SizeLimitExceeded(17));
+        assert!(Response::read(&mut stream.as_bytes(), Some(19)).await.is_ok());
+    }
+
+    #[async_std::test]
+    async fn serializes_to_bytes() {
+        let mut res = Response::new();
+        res.set_cookie(&Cookie::new("id", "1")).unwrap();
+        assert_eq!(res.to_bytes(), b"HTTP/1.1 200 OK\r\nSet-Cookie: id=1\r\n\r\n");
+        assert_eq!(res.to_bytes(), res.to_string().into_bytes());
+    }
+
+    #[async_std::test]
+    async fn sets_keep_alive_per_version() {
+        let mut res = Response::new();
+        assert!(res.is_keep_alive());
+        res.set_keep_alive(false);
+        assert_eq!(res.header("Connection").unwrap(), "close");
+        assert!(!res.is_keep_alive());
+        res.set_keep_alive(true);
+        assert!(!res.has_header("Connection"));
+
+        res.set_version("HTTP/1.0");
+        assert!(!res.is_keep_alive());
+        res.set_keep_alive(true);
+        assert_eq!(res.header("Connection").unwrap(), "keep-alive");
+        assert!(res.is_keep_alive());
+        res.set_keep_alive(false);
+        assert!(!res.has_header("Connection"));
+
+        let mut res = Response::new();
+        res.set_header("connection", "Upgrade, keep-alive");
+        res.set_keep_alive(false);
+        assert_eq!(res.headers().len(), 1);
+        assert_eq!(res.header("Connection").unwrap(), "Upgrade, close");
+        res.set_keep_alive(true);
+        assert_eq!(res.header("Connection").unwrap(), "Upgrade");
+    }
+
+    #[async_std::test]
+    async fn estimates_body_length() {
+        let mut res = Response::new();
+        assert_eq!(res.expected_body_length(), None);
+        res.set_header("Content-Length", "42");
+        assert_eq!(res.expected_body_length(), Some(42));
+        res.set_header("Transfer-Encoding", "chunked");
+        assert_eq!(res.expected_body_length(), None);
+    }
+
+    #[async_std::test]
+    async fn copies_headers_from_request() {
+        let mut req = Request::new();
+        req.set_header("Origin", "https://a.com");
+        req.set_header("H", "1");
+        req.set_header("Content-Length", "5");
+        req.set_header("Connection", "X-Hop");
+        req.set_header("X-Hop", "1");
+        let mut res = Response::new();
+        res.set_header("H", "0");
+        res.copy_headers_from(&req);
+        assert_eq!(res.header("Origin").unwrap(), "https://a.com");
+        assert_eq!(res.header("H").unwrap(), "0, 1");
+        assert_eq!(res.headers().len(), 2);
+        res.set_header("Transfer-Encoding", "chunked");
+        res.set_cookie(&Cookie::new("a", "1")).unwrap();
+        res.set_cookie(&Cookie::new("b", "2")).unwrap();
+        let mut req = Request::new();
+        req.copy_headers_from(&res);
+        assert_eq!(req.headers().len(), 3);
+        assert_eq!(req.header("Set-Cookie").unwrap(), "a=1, b=2");
+    }
+
+    #[async_std::test]
+    async fn builds_status_line() {
+        let mut res = Response::new();
+        assert_eq!(res.status_line(), "HTTP/1.1 200 OK");
+        res.set_status_code_with_default_message(404);
+        assert_eq!(res.status_line(), "HTTP/1.1 404 Not Found");
+    }
+
+    #[async_std::test]
+    async fn sets_www_authenticate() {
+        let mut res = Response::new();
+        res.www_authenticate("Basic", "Admin \"area\"");
+        assert_eq!(res.header("WWW-Authenticate").unwrap(), "Basic realm=\"Admin \\\"area\\\"\"");
+    }
+
+    #[async_std::test]
+    async fn resets_for_reuse() {
+        let mut res = Response::new();
+        res.set_status_code_with_default_message(404);
+        res.set_version("HTTP/1.0");
+        res.set_header("H", "V");
+        res.set_cookie(&Cookie::new("id", "1")).unwrap();
+        res.reset();
+        assert_eq!(res.to_string(), "HTTP/1.1 200 OK\r\n\r\n");
+    }
+
+    #[async_std::test]
+    async fn builds_method_not_allowed() {
+        let res = Response::method_not_allowed(&["GET", "HEAD"]);
+        assert_eq!(res.status_line(), "HTTP/1.1 405 Method Not Allowed");
+        assert_eq!(res.header("Allow").unwrap(), "GET, HEAD");
+    }
+
+    #[async_std::test]
+    async fn writes_with_matching_body_length() {
+        let mut res = Response::new();
+        let mut output = Vec::new();
+        res.write_with_body(&mut output, &mut Body::from("Hello")).await.unwrap();
+        assert_eq!(res.header("Content-Length").unwrap(), "5");
+        assert!(output.ends_with(b"\r\n\r\nHello"));
+        res.set_header("Content-Length", "10");
+        let res = res.write_with_body(&mut Vec::new(), &mut Body::from("Hello")).await;
+        assert_eq!(res, Err(Error::InvalidData));
+    }
+
+    #[async_std::test]
+    async fn writes_bodyless_and_chunked_statuses() {
+        let mut res = Response::new();
+        res.set_status_code(204);
+        let mut output = Vec::new();
+        res.write_with_body(&mut output, &mut Body::new()).await.unwrap();
+        assert!(!res.has_header("Content-Length"));
+        assert!(output.ends_with(b"\r\n\r\n"));
+        let err = res.write_with_body(&mut Vec::new(), &mut Body::from("Hello")).await;
+        assert_eq!(err, Err(Error::InvalidData));
+        res.set_status_code(304);
+        res.write_with_body(&mut Vec::new(), &mut Body::new()).await.unwrap();
+        assert!(!res.has_header("Content-Length"));
+        res.set_status_code(100);
+        res.write_with_body(&mut Vec::new(), &mut Body::new()).await.unwrap();
+        assert!(!res.has_header("Content-Length"));
+
+        let mut res = Response::new();
+        res.set_chunked();
+        let err = res.write_with_body(&mut Vec::new(), &mut Body::from("Hello")).await;
+        assert_eq!(err, Err(Error::InvalidData));
+    }
+
+    #[async_std::test]
+    async fn advertises_trailers() {
+        let mut res = Response::new();
+        res.set_trailer(&["grpc-status", "grpc-message"]);
+        assert_eq!(res.header("Trailer").unwrap(), "grpc-status, grpc-message");
+    }
+
+    #[async_std::test]
+    async fn sets_status_with_default_message() {
+        let mut res = Response::new();
+        res.set_status_code_with_default_message(404);
+        assert_eq!(res.status_code(), 404);
+        assert_eq!(res.status_message(), "Not Found");
+        res.set_status_code_with_default_message(299);
+        assert_eq!(res.status_message(), "");
+    }
+
+    #[async_std::test]
+    async fn reads_interim_responses() {
+        let mut stream = "HTTP/1.1 103 Early Hints\r\nLink: </a>\r\n\r\nHTTP/1.1 200 OK\r\n\r\n".as_bytes();
+        let (res, interim) = Response::read_interim(&mut stream, None).await.unwrap();
+        assert!(interim);
+        assert_eq!(res.status_code(), 103);
+        let (res, interim) = Response::read_interim(&mut stream, None).await.unwrap();
+        assert!(!interim);
+        assert_eq!(res.status_code(), 200);
+    }
+
+    #[async_std::test]
+    async fn sets_cookies() {
+        let mut res = Response::new();
+        let mut cookie = Cookie::new("id", "a3fWa");
+        cookie.set_path("/");
+        cookie.set_max_age(3600);
+        cookie.set_secure(true);
+        cookie.set_http_only(true);
+        cookie.set_same_site(SameSite::Lax);
+        res.set_cookie(&cookie).unwrap();
+        res.set_cookie(&Cookie::new("lang", "en")).unwrap();
+        assert_eq!(res.to_string(), "HTTP/1.1 200 OK\r\n\
+            Set-Cookie: id=a3fWa; Path=/; Max-Age=3600; Secure; HttpOnly; SameSite=Lax\r\n\
+            Set-Cookie: lang=en\r\n\r\n");
+        assert!(res.has_header("Set-Cookie"));
+        assert_eq!(res.cookies(), vec!["id=a3fWa; Path=/; Max-Age=3600; Secure; HttpOnly; SameSite=Lax", "lang=en"]);
+        assert_eq!(res.set_cookie(&Cookie::new("id", "1\r\nX: y")), Err(Error::InvalidData));
+        assert_eq!(res.cookies().len(), 2);
+
+        let (_, _, _, headers) = res.into_parts();
+        assert_eq!(headers.get("Set-Cookie").unwrap(), "id=a3fWa; Path=/; Max-Age=3600; Secure; HttpOnly; SameSite=Lax");
+        let mut res = Response::new();
+        res.set_cookie(&Cookie::new("id", "1")).unwrap();
+        res.clear_cookies();
+        assert!(res.cookies().is_empty());
+    }
+
+    #[async_std::test]
+    async fn reads_cookies_from_wire() {
+        let mut stream = "HTTP/1.1 200 OK\r\nSet-Cookie: a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT\r\nSet-Cookie: b=2\r\n\r\n".as_bytes();
+        let res = Response::read(&mut stream, None).await.unwrap();
+        assert_eq!(res.cookies(), vec!["a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT", "b=2"]);
+        assert_eq!(res.to_bytes(), b"HTTP/1.1 200 OK\r\nSet-Cookie: a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT\r\nSet-Cookie: b=2\r\n\r\n");
+        assert_eq!(res.header_values("Set-Cookie"), vec!["a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT", "b=2"]);
+        assert_eq!(res.headers_iter().count(), 2);
+        assert!(res.headers_iter().all(|(_, value)| !value.contains('\n')));
+        assert_eq!(res.header("Set-Cookie").unwrap(), "a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+
+        let mut res = res;
+        res.set_header("Set-Cookie", "c=3");
+        assert_eq!(res.cookies(), vec!["c=3"]);
+        res.append_header("Set-Cookie", "d=4");
+        res.remove_header("Set-Cookie");
+        assert!(res.cookies().is_empty());
+    }
+
+    #[async_std::test]
+    async fn appends_header_values() {
+        let mut res = Response::new();
+        res.append_header("Via", "1.1 a");
+        res.append_header("Via", "1.1 b");
+        assert_eq!(res.header("Via").unwrap(), "1.1 a, 1.1 b");
+        res.append_header("Set-Cookie", "a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT");
+        res.append_header("Set-Cookie", "b=2");
+        assert_eq!(res.cookies(), vec!["a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT", "b=2"]);
+        assert!(res.to_string().contains("\r\nSet-Cookie: a=1; Expires=Wed, 21 Oct 2015 07:28:00 GMT\r\nSet-Cookie: b=2\r\n"));
+    }
+
+    #[async_std::test]
+    async fn checks_status_category() {
+        let mut res = Response::new();
+        assert!(res.is_success());
+        res.set_status_code(199);
+        assert!(res.is_informational() && !res.is_success());
+        res.set_status_code(301);
+        assert!(res.is_redirection());
+        res.set_status_code(404);
+        assert!(res.is_client_error());
+        res.set_status_code(599);
+        assert!(res.is_server_error());
+    }
+
+    #[async_std::test]
+    async fn sets_and_reads_retry_after() {
+        let mut res = Response::new();
+        res.retry_after(Duration::from_secs(30));
+        assert_eq!(res.header("Retry-After").unwrap(), "30");
+        assert_eq!(res.retry_after_value(), Some(RetryAfter::Delay(Duration::from_secs(30))));
+        let date = UNIX_EPOCH + Duration::from_secs(784111777);
+        res.retry_after(date);
+        assert_eq!(res.header("Retry-After").unwrap(), "Sun, 06 Nov 1994 08:49:37 GMT");
+        assert_eq!(res.retry_after_value(), Some(RetryAfter::Date(date)));
+    }
+
+    #[async_std::test]
+    async fn round_trips_through_bytes() {
+        for (code, headers) in [
+            (200, vec![]),
+            (204, vec![("Date", "Sun, 06 Nov 1994 08:49:37 GMT")]),
+            (301, vec![("Location", "/b"), ("X-List", "a, b")]),
+        ] {
+            let mut res = Response::new();
+            res.set_status_code(code);
+            for (name, value) in headers {
+                res.set_header(name, value);
+            }
+            let other = round_trip(&res).await;
+            assert_eq!(other.status_code(), res.status_code());
+            assert_eq!(other.status_message(), res.status_message());
+            assert_eq!(other.version(), res.version());
+            assert_eq!(other.headers(), res.headers()); // HashMap equality ignores order
+        }
+    }
+
+    #[async_std::test]
+    async fn reads_content_range() {
+        let mut res = Response::new();
+        assert_eq!(res.content_range(), None);
+        res.set_status_code(206);
+        res.set_header("Content-Range", "bytes 0-99/1000");
+        assert_eq!(res.content_range(), Some(ContentRange::Bytes { start: 0, end: 99, total: Some(1000) }));
+    }
+
+    #[async_std::test]
+    async fn reads_optional_reason_phrase() {
+        let res = Response::read(&mut "HTTP/1.1 204 \r\n\r\n".as_bytes(), None).await.unwrap();
+        assert_eq!(res.status_code(), 204);
+        assert_eq!(res.status_message(), "");
+        let res = Response::read(&mut "HTTP/1.1 200\r\n\r\n".as_bytes(), None).await.unwrap();
+        assert_eq!(res.status_message(), "");
+        let res = Response::read(&mut "HTTP/1.1 404 Not Found\r\n\r\n".as_bytes(), None).await.unwrap();
+        assert_eq!(res.status_message(), "Not Found");
+    }
+
+    #[async_std::test]
+    async fn keeps_framing_headers_exclusive() {
+        let mut res = Response::new();
+        res.set_header("content-length", "5");
+        res.set_chunked();
+        assert_eq!(res.headers().len(), 1);
+        assert_eq!(res.header("Transfer-Encoding").unwrap(), "chunked");
+        res.set_content_length(7);
+        assert_eq!(res.headers().len(), 1);
+        assert_eq!(res.header("Content-Length").unwrap(), "7");
+        assert_eq!(res.expected_body_length(), Some(7));
+        res.set_header("transfer-encoding", "gzip");
+        res.set_header("content-length", "7");
+        res.set_chunked();
+        assert_eq!(res.headers().len(), 1);
+        assert_eq!(res.header("Transfer-Encoding").unwrap(), "chunked");
+        res.set_header("content-length", "9");
+        res.set_content_length(7);
+        assert_eq!(res.headers().len(), 1);
+        assert_eq!(res.header("Content-Length").unwrap(), "7");
+    }
+
+    #[async_std::test]
+    async fn builds_redirects() {
+        let res = Response::redirect(307, "/b").unwrap();
+        assert_eq!(res.status_line(), "HTTP/1.1 307 Temporary Redirect");
+        assert_eq!(res.location().unwrap(), "/b");
+        assert_eq!(Response::redirect(200, "/b").unwrap_err(), Error::InvalidData);
+        assert_eq!(Response::found("/c").status_line(), "HTTP/1.1 302 Found");
+        let res = Response::moved_permanently("http://a.com/");
+        assert_eq!(res.status_line(), "HTTP/1.1 301 Moved Permanently");
+        assert_eq!(res.header("Location").unwrap(), "http://a.com/");
+    }
 }
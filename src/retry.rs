@@ -0,0 +1,53 @@
+use std::time::{Duration, SystemTime};
+use crate::{format_http_date, parse_http_date};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RetryAfter {
+    Delay(Duration),
+    Date(SystemTime),
+}
+
+impl RetryAfter {
+
+    pub fn parse(value: &str) -> Option<Self> {
+        let value = value.trim();
+        if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+            value.parse::<u64>().ok().map(|secs| RetryAfter::Delay(Duration::from_secs(secs)))
+        } else {
+            parse_http_date(value).map(RetryAfter::Date)
+        }
+    }
+
+    pub fn to_header_value(&self) -> String {
+        match self {
+            RetryAfter::Delay(delay) => delay.as_secs().to_string(),
+            RetryAfter::Date(date) => format_http_date(*date),
+        }
+    }
+}
+
+impl From<Duration> for RetryAfter {
+    fn from(delay: Duration) -> Self {
+        RetryAfter::Delay(delay)
+    }
+}
+
+impl From<SystemTime> for RetryAfter {
+    fn from(date: SystemTime) -> Self {
+        RetryAfter::Date(date)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    #[async_std::test]
+    async fn parses_both_forms() {
+        assert_eq!(RetryAfter::parse("120"), Some(RetryAfter::Delay(Duration::from_secs(120))));
+        assert_eq!(RetryAfter::parse("Sun, 06 Nov 1994 08:49:37 GMT"), Some(RetryAfter::Date(UNIX_EPOCH + Duration::from_secs(784111777))));
+        assert_eq!(RetryAfter::parse("-1"), None);
+        assert_eq!(RetryAfter::parse(""), None);
+    }
+}
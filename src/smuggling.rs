@@ -0,0 +1,6 @@
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmugglingKind {
+    ContentLengthAndTransferEncoding,
+    DuplicateContentLength,
+    ObfuscatedTransferEncoding,
+}
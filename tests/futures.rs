@@ -0,0 +1,49 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use futures_io::{AsyncRead, AsyncWrite};
+use async_httype::{Body, Request, Response};
+
+struct Duplex {
+    input: Vec<u8>,
+    position: usize,
+    output: Vec<u8>,
+}
+
+impl AsyncRead for Duplex {
+    fn poll_read(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let size = std::cmp::min(3, std::cmp::min(buf.len(), self.input.len() - self.position)); // short reads
+        buf[0..size].copy_from_slice(&self.input[self.position..self.position + size]);
+        self.position += size;
+        Poll::Ready(Ok(size))
+    }
+}
+
+impl AsyncWrite for Duplex {
+    fn poll_write(mut self: Pin<&mut Self>, _: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        self.output.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[async_std::test]
+async fn works_with_futures_io_streams() {
+    let input = b"POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nHello".to_vec();
+    let mut stream = Duplex { input, position: 0, output: Vec::new() };
+    let req = Request::read(&mut stream, None).await.unwrap();
+    let mut body = Body::new();
+    body.read(&mut stream, req.headers()).await.unwrap();
+    assert_eq!(body.bytes(), b"Hello");
+
+    let mut res = Response::new();
+    res.write_with_body(&mut stream, &mut body).await.unwrap();
+    assert_eq!(stream.output, b"HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\nHello");
+}
@@ -0,0 +1,40 @@
+#![cfg(unix)]
+
+use std::process::{Command, Stdio};
+use async_std::fs::File;
+use async_httype::{Body, Request, Response};
+
+fn pipe(script: &str) -> File {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(script)
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let stdout = child.stdout.take().unwrap();
+    std::thread::spawn(move || child.wait());
+    File::from(std::fs::File::from(std::os::fd::OwnedFd::from(stdout)))
+}
+
+#[async_std::test]
+async fn reads_request_from_short_reads() {
+    let mut input = pipe("printf 'POST /a HT'; sleep 0.05; printf 'TP/1.1\\r\\nContent-Len'; sleep 0.05; printf 'gth: 5\\r\\n\\r\\nHel'; sleep 0.05; printf 'lo'");
+    let req = Request::read(&mut input, None).await.unwrap();
+    assert_eq!(req.uri(), "/a");
+    let mut body = Body::new();
+    body.read(&mut input, req.headers()).await.unwrap();
+    assert_eq!(body.bytes(), b"Hello");
+}
+
+#[async_std::test]
+async fn reads_response_until_pipe_closes() {
+    let mut input = pipe("printf 'HTTP/1.1 200 OK\\r\\nX-A: 1\\r\\n\\r\\n'");
+    let res = Response::read(&mut input, None).await.unwrap();
+    assert_eq!(res.header("X-A").unwrap(), "1");
+}
+
+#[async_std::test]
+async fn rejects_request_cut_by_eof() {
+    let mut input = pipe("printf 'GET /a HTTP/1.'");
+    assert!(Request::read(&mut input, None).await.is_err());
+}
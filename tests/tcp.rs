@@ -0,0 +1,99 @@
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::task;
+use async_httype::{Body, Relay, Request, Response};
+
+async fn serve(listener: TcpListener, requests: usize) {
+    let (mut stream, _) = listener.accept().await.unwrap();
+    for _ in 0..requests {
+        let req = Request::read(&mut stream, None).await.unwrap();
+        let mut body = Body::new();
+        body.read(&mut stream, req.headers()).await.unwrap();
+
+        let mut res = Response::new();
+        res.set_header("Content-Length", body.length().to_string());
+        res.write(&mut stream).await.unwrap();
+        body.write(&mut stream).await.unwrap();
+    }
+}
+
+async fn request(stream: &mut TcpStream, data: &str) -> (Response, Body) {
+    stream.write_all(data.as_bytes()).await.unwrap();
+    let res = Response::read(stream, None).await.unwrap();
+    let mut body = Body::new();
+    body.read(stream, res.headers()).await.unwrap();
+    (res, body)
+}
+
+#[async_std::test]
+async fn serves_sized_request() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = task::spawn(serve(listener, 1));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let (res, body) = request(&mut stream, "POST / HTTP/1.1\r\nContent-Length: 5\r\n\r\nHello").await;
+    assert_eq!(res.status_code(), 200);
+    assert_eq!(res.header("Content-Length").unwrap(), "5");
+    assert_eq!(body.bytes(), b"Hello");
+
+    server.await;
+}
+
+#[async_std::test]
+async fn serves_chunked_request_over_keep_alive() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = task::spawn(serve(listener, 2));
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    let (_, body) = request(&mut stream, "POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n6\r\nHello \r\n6\r\nWorld!\r\n0\r\n\r\n").await;
+    assert_eq!(body.bytes(), b"Hello World!");
+    let (_, body) = request(&mut stream, "POST / HTTP/1.1\r\nContent-Length: 3\r\n\r\nBye").await;
+    assert_eq!(body.bytes(), b"Bye");
+
+    server.await;
+}
+
+#[async_std::test]
+async fn fails_on_closed_connection() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let client = task::spawn(async move {
+        let mut stream = TcpStream::connect(addr).await.unwrap();
+        stream.write_all(b"POST / HTTP/1.1\r\nContent-Length: 10\r\n\r\nHel").await.unwrap();
+    });
+
+    let (mut stream, _) = listener.accept().await.unwrap();
+    client.await;
+    let req = Request::read(&mut stream, None).await.unwrap();
+    let mut body = Body::new();
+    assert!(body.read(&mut stream, req.headers()).await.is_err());
+}
+
+#[async_std::test]
+async fn relays_body_while_responding() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = task::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let (mut reader, mut writer) = (&stream, &stream);
+        let req = Request::read(&mut reader, None).await.unwrap();
+
+        let mut res = Response::new();
+        res.set_header("Transfer-Encoding", "chunked");
+        res.write(&mut writer).await.unwrap();
+        Relay::new().relay(&mut reader, &mut writer, req.headers()).await.unwrap()
+    });
+
+    let mut stream = TcpStream::connect(addr).await.unwrap();
+    stream.write_all(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n").await.unwrap();
+    let res = Response::read(&mut stream, None).await.unwrap();
+    assert_eq!(res.status_code(), 200);
+    stream.write_all(b"0\r\n\r\n").await.unwrap();
+    let mut body = Body::new();
+    body.read(&mut stream, res.headers()).await.unwrap();
+    assert_eq!(body.bytes(), b"Hello");
+
+    assert_eq!(server.await, 15);
+}
@@ -0,0 +1,65 @@
+use std::convert::TryFrom;
+use std::sync::Arc;
+use async_std::net::{TcpListener, TcpStream};
+use async_std::prelude::*;
+use async_std::task;
+use futures_rustls::{TlsAcceptor, TlsConnector};
+use futures_rustls::pki_types::{PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+use futures_rustls::rustls::{ClientConfig, RootCertStore, ServerConfig};
+use futures_rustls::rustls::crypto::ring::default_provider;
+use async_httype::{Body, Request, Response};
+
+fn tls_pair() -> (TlsAcceptor, TlsConnector) {
+    let cert = rcgen::generate_simple_self_signed(vec![String::from("localhost")]).unwrap();
+    let cert_der = cert.cert.der().clone();
+    let key_der = PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(cert.key_pair.serialize_der()));
+    let provider = Arc::new(default_provider());
+
+    let server = ServerConfig::builder_with_provider(provider.clone())
+        .with_safe_default_protocol_versions().unwrap()
+        .with_no_client_auth()
+        .with_single_cert(vec![cert_der.clone()], key_der).unwrap();
+
+    let mut roots = RootCertStore::empty();
+    roots.add(cert_der).unwrap();
+    let client = ClientConfig::builder_with_provider(provider)
+        .with_safe_default_protocol_versions().unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+
+    (TlsAcceptor::from(Arc::new(server)), TlsConnector::from(Arc::new(client)))
+}
+
+#[async_std::test]
+async fn serves_request_over_tls() {
+    let (acceptor, connector) = tls_pair();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    let server = task::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let mut stream = acceptor.accept(stream).await.unwrap();
+        let req = Request::read(&mut stream, None).await.unwrap();
+        let mut body = Body::new();
+        body.read(&mut stream, req.headers()).await.unwrap();
+
+        let mut res = Response::new();
+        res.set_header("Content-Length", body.length().to_string());
+        res.write(&mut stream).await.unwrap();
+        body.write(&mut stream).await.unwrap();
+    });
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let domain = ServerName::try_from("localhost").unwrap();
+    let mut stream = connector.connect(domain, stream).await.unwrap();
+    stream.write_all(b"POST / HTTP/1.1\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nHello\r\n0\r\n\r\n").await.unwrap();
+    stream.flush().await.unwrap();
+
+    let res = Response::read(&mut stream, None).await.unwrap();
+    let mut body = Body::new();
+    body.read(&mut stream, res.headers()).await.unwrap();
+    assert_eq!(res.status_code(), 200);
+    assert_eq!(body.bytes(), b"Hello");
+
+    server.await;
+}